@@ -0,0 +1,24 @@
+// Covers the part of a node that is still cooling down, clockwise from the top
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+
+const TAU: f32 = 6.28318530718;
+
+struct CooldownMaterial {
+    color: vec4<f32>,
+    // Only x is used, between 0 when the cooldown starts and 1 when it is over
+    progress: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> material: CooldownMaterial;
+
+@fragment
+fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {
+    let offset = in.uv - vec2<f32>(0.5);
+    // 0 at the top, going around clockwise to 1
+    let turn = fract(atan2(offset.x, -offset.y) / TAU + 1.0);
+    if turn < material.progress.x {
+        return vec4<f32>(0.0);
+    }
+    return material.color;
+}
@@ -0,0 +1,307 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::data::{DataAsset, DataAssetApp};
+use crate::enemy::EnemyKind;
+use crate::item::Weapon;
+use crate::loading::DataAssets;
+use crate::menu::leaderboard::unix_time;
+use crate::menu::Score;
+use crate::player::Player;
+use crate::run::{RunFinished, RunStats};
+use crate::settings::Settings;
+use crate::storage;
+use crate::GameState;
+
+const UNLOCKED_FILE: &str = "unlocked_achievements.toml";
+const TOAST_DURATION: f32 = 4.;
+const TOAST_HEIGHT: f32 = 60.;
+
+pub struct AchievementsPlugin;
+
+/// What has to happen within a single run to unlock an achievement
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    Kills(u32),
+    EnemyKills {
+        kind: EnemyKind,
+        count: u32,
+    },
+    WeaponKills {
+        weapon: Weapon,
+        count: u32,
+    },
+    Level(i32),
+    /// Seconds alive
+    Survive(f32),
+    /// Seconds alive without taking any damage
+    NoHit(f32),
+    Score(i32),
+}
+
+/// How far the current run got
+struct Progress<'a> {
+    stats: &'a RunStats,
+    level: i32,
+    score: i32,
+}
+
+impl Condition {
+    fn is_met(&self, progress: &Progress) -> bool {
+        let stats = progress.stats;
+        match self {
+            Condition::Kills(count) => stats.kills >= *count,
+            Condition::EnemyKills { kind, count } => {
+                stats.kills_by_enemy.get(kind).copied().unwrap_or(0) >= *count
+            }
+            Condition::WeaponKills { weapon, count } => {
+                stats.kills_by_weapon.get(weapon).copied().unwrap_or(0) >= *count
+            }
+            Condition::Level(level) => progress.level >= *level,
+            Condition::Survive(seconds) => stats.time_alive >= *seconds,
+            Condition::NoHit(seconds) => stats.damage_taken == 0. && stats.time_alive >= *seconds,
+            Condition::Score(score) => progress.score >= *score,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Achievement {
+    /// Stays the same when the name changes, unlocks are saved by it
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub condition: Condition,
+}
+
+/// Every achievement there is, from `assets/data/game.achievements.toml`
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct AchievementList {
+    #[serde(rename = "achievement")]
+    pub achievements: Vec<Achievement>,
+}
+
+impl DataAsset for AchievementList {
+    const EXTENSION: &'static str = "achievements.toml";
+
+    fn validate(&self) -> Result<(), String> {
+        let mut ids = HashSet::new();
+        for achievement in &self.achievements {
+            if achievement.id.is_empty() || achievement.name.is_empty() {
+                return Err("every achievement needs an id and a name".to_string());
+            }
+            if !ids.insert(&achievement.id) {
+                return Err(format!(
+                    "the achievement id {} is used twice",
+                    achievement.id
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Unlock {
+    pub id: String,
+    /// Seconds since the Unix epoch
+    pub date: u64,
+}
+
+/// Achievements unlocked on this device, saved whenever one is added
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct UnlockedAchievements {
+    unlocks: Vec<Unlock>,
+}
+
+impl UnlockedAchievements {
+    fn load() -> Self {
+        let Some(contents) = storage::load(UNLOCKED_FILE) else {
+            return UnlockedAchievements::default();
+        };
+        toml::from_str(&contents).unwrap_or_else(|error| {
+            warn!("Ignoring invalid {UNLOCKED_FILE}: {error}");
+            UnlockedAchievements::default()
+        })
+    }
+
+    fn save(&self) {
+        match toml::to_string(self) {
+            Ok(contents) => storage::save(UNLOCKED_FILE, &contents),
+            Err(error) => warn!("Failed to save achievements: {error}"),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Unlock> {
+        self.unlocks.iter().find(|unlock| unlock.id == id)
+    }
+
+    /// Returns whether it was locked before
+    fn unlock(&mut self, id: &str) -> bool {
+        if self.get(id).is_some() {
+            return false;
+        }
+        self.unlocks.push(Unlock {
+            id: id.to_string(),
+            date: unix_time(),
+        });
+        self.save();
+        true
+    }
+}
+
+#[derive(Event)]
+pub struct AchievementUnlocked(pub Achievement);
+
+#[derive(Component)]
+struct Toast(Timer);
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_data_asset::<AchievementList>()
+            .insert_resource(UnlockedAchievements::load())
+            .add_event::<AchievementUnlocked>()
+            .add_systems(
+                Update,
+                check_achievements.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (check_finished_runs, show_toasts, hide_toasts)
+                    .chain()
+                    .run_if(resource_exists::<DataAssets>),
+            );
+    }
+}
+
+fn unlock_met(
+    achievements: &AchievementList,
+    unlocked: &mut UnlockedAchievements,
+    progress: &Progress,
+    unlocked_events: &mut EventWriter<AchievementUnlocked>,
+) {
+    for achievement in &achievements.achievements {
+        if unlocked.get(&achievement.id).is_none()
+            && achievement.condition.is_met(progress)
+            && unlocked.unlock(&achievement.id)
+        {
+            info!("Achievement unlocked: {}", achievement.name);
+            unlocked_events.send(AchievementUnlocked(achievement.clone()));
+        }
+    }
+}
+
+fn check_achievements(
+    data: Res<DataAssets>,
+    lists: Res<Assets<AchievementList>>,
+    stats: Res<RunStats>,
+    score: Res<Score>,
+    player_query: Query<&Player>,
+    mut unlocked: ResMut<UnlockedAchievements>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+) {
+    let (Some(achievements), Ok(player)) =
+        (lists.get(&data.achievements), player_query.get_single())
+    else {
+        return;
+    };
+    let progress = Progress {
+        stats: &stats,
+        level: player.level.value,
+        score: score.score,
+    };
+    unlock_met(achievements, &mut unlocked, &progress, &mut unlocked_events);
+}
+
+/// Catches what happened on the frame the run ended
+fn check_finished_runs(
+    data: Res<DataAssets>,
+    lists: Res<Assets<AchievementList>>,
+    mut unlocked: ResMut<UnlockedAchievements>,
+    mut finished_events: EventReader<RunFinished>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+) {
+    let Some(achievements) = lists.get(&data.achievements) else {
+        return;
+    };
+    for RunFinished(report) in finished_events.read() {
+        let progress = Progress {
+            stats: &report.stats,
+            level: report.entry.level,
+            score: report.entry.score,
+        };
+        unlock_met(achievements, &mut unlocked, &progress, &mut unlocked_events);
+    }
+}
+
+fn show_toasts(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    toast_query: Query<(), With<Toast>>,
+    mut unlocked_events: EventReader<AchievementUnlocked>,
+) {
+    // New toasts go below the ones still showing
+    let shown = toast_query.iter().count();
+    for (slot, AchievementUnlocked(achievement)) in (shown..).zip(unlocked_events.read()) {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(10. + slot as f32 * TOAST_HEIGHT),
+                        left: Val::Percent(50.),
+                        width: Val::Px(320.),
+                        margin: UiRect::left(Val::Px(-160.)),
+                        padding: UiRect::all(Val::Px(8.)),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.1, 0.1, 0.1, 0.85).into(),
+                    border_color: Color::GOLD.into(),
+                    z_index: ZIndex::Global(10),
+                    ..default()
+                },
+                Toast(Timer::from_seconds(TOAST_DURATION, TimerMode::Once)),
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    settings.language.tr("Achievement unlocked"),
+                    TextStyle {
+                        font_size: 14.0,
+                        color: Color::GOLD,
+                        ..default()
+                    },
+                ));
+                parent.spawn(TextBundle::from_section(
+                    achievement.name.clone(),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ));
+            });
+    }
+}
+
+fn hide_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toast_query: Query<(Entity, &mut Toast, &mut BackgroundColor)>,
+) {
+    for (entity, mut toast, mut background) in &mut toast_query {
+        toast.0.tick(time.delta());
+        if toast.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            // Fade out over the last second
+            let alpha = toast.0.remaining_secs().min(1.) * 0.85;
+            background.0.set_a(alpha);
+        }
+    }
+}
@@ -5,25 +5,27 @@ pub enum GameControl {
     Down,
     Left,
     Right,
+    Dash,
 }
 
 impl GameControl {
-    pub fn pressed(&self, keyboard_input: &Res<ButtonInput<KeyCode>>) -> bool {
+    fn keys(&self) -> [KeyCode; 2] {
         match self {
-            GameControl::Up => {
-                keyboard_input.pressed(KeyCode::KeyW) || keyboard_input.pressed(KeyCode::ArrowUp)
-            }
-            GameControl::Down => {
-                keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown)
-            }
-            GameControl::Left => {
-                keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft)
-            }
-            GameControl::Right => {
-                keyboard_input.pressed(KeyCode::KeyD) || keyboard_input.pressed(KeyCode::ArrowRight)
-            }
+            GameControl::Up => [KeyCode::KeyW, KeyCode::ArrowUp],
+            GameControl::Down => [KeyCode::KeyS, KeyCode::ArrowDown],
+            GameControl::Left => [KeyCode::KeyA, KeyCode::ArrowLeft],
+            GameControl::Right => [KeyCode::KeyD, KeyCode::ArrowRight],
+            GameControl::Dash => [KeyCode::ShiftLeft, KeyCode::ShiftRight],
         }
     }
+
+    pub fn pressed(&self, keyboard_input: &Res<ButtonInput<KeyCode>>) -> bool {
+        keyboard_input.any_pressed(self.keys())
+    }
+
+    pub fn just_pressed(&self, keyboard_input: &Res<ButtonInput<KeyCode>>) -> bool {
+        keyboard_input.any_just_pressed(self.keys())
+    }
 }
 
 pub fn get_movement(control: GameControl, input: &Res<ButtonInput<KeyCode>>) -> f32 {
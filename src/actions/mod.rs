@@ -8,6 +8,8 @@ use crate::GameState;
 mod game_control;
 
 pub const FOLLOW_EPSILON: f32 = 5.;
+// How far a stick has to be pushed before the player moves
+const STICK_DEAD_ZONE: f32 = 0.2;
 
 pub struct ActionsPlugin;
 
@@ -19,6 +21,7 @@ impl Plugin for ActionsPlugin {
             Update,
             (
                 set_movement_actions.run_if(in_state(GameState::Playing)),
+                set_dash_action.run_if(in_state(GameState::Playing)),
                 // set_gun_actions.run_if(in_state(GameState::Playing)),
             ),
         );
@@ -28,15 +31,26 @@ impl Plugin for ActionsPlugin {
 #[derive(Default, Resource)]
 pub struct Actions {
     pub player_movement: Option<Vec2>,
+    /// Dash was pressed this frame
+    pub dash: bool,
     // pub enemy_movement: Option<Vec2>,
 }
 
+/// The on screen button that dashes, touches on it don't move the player
+#[derive(Component)]
+pub struct DashButton;
+
+#[allow(clippy::too_many_arguments)]
 pub fn set_movement_actions(
     mut actions: ResMut<Actions>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
     touch_input: Res<Touches>,
     player: Query<&Transform, With<Player>>,
     camera: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    dash_button: Query<(&Node, &GlobalTransform), With<DashButton>>,
 ) {
     let mut player_movement = Vec2::new(
         get_movement(GameControl::Right, &keyboard_input)
@@ -45,7 +59,42 @@ pub fn set_movement_actions(
             - get_movement(GameControl::Down, &keyboard_input),
     );
 
-    if let Some(touch_position) = touch_input.first_pressed_position() {
+    for gamepad in gamepads.iter() {
+        let pressed = |button_type| {
+            if gamepad_buttons.pressed(GamepadButton::new(gamepad, button_type)) {
+                1.
+            } else {
+                0.
+            }
+        };
+        let axis = |axis_type| {
+            gamepad_axes
+                .get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or(0.)
+        };
+        player_movement += Vec2::new(
+            pressed(GamepadButtonType::DPadRight) - pressed(GamepadButtonType::DPadLeft),
+            pressed(GamepadButtonType::DPadUp) - pressed(GamepadButtonType::DPadDown),
+        );
+        let stick = Vec2::new(
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        );
+        if stick.length() > STICK_DEAD_ZONE {
+            player_movement += stick;
+        }
+    }
+
+    let on_dash_button = |position: Vec2| {
+        dash_button
+            .iter()
+            .any(|(node, transform)| node.logical_rect(transform).contains(position))
+    };
+    if let Some(touch_position) = touch_input
+        .iter()
+        .map(|touch| touch.position())
+        .find(|position| !on_dash_button(*position))
+    {
         let (camera, camera_transform) = camera.single();
         if let Some(touch_position) = camera.viewport_to_world_2d(camera_transform, touch_position)
         {
@@ -62,3 +111,23 @@ pub fn set_movement_actions(
         actions.player_movement = None;
     }
 }
+
+pub fn set_dash_action(
+    mut actions: ResMut<Actions>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    dash_button: Query<&Interaction, (Changed<Interaction>, With<DashButton>)>,
+) {
+    let gamepad_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    actions.dash = GameControl::Dash.just_pressed(&keyboard_input)
+        || gamepad_pressed(GamepadButtonType::South)
+        || gamepad_pressed(GamepadButtonType::RightTrigger)
+        || dash_button
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed);
+}
@@ -0,0 +1,186 @@
+//! Frame animations for sprites drawn from a texture atlas. Every sheet comes with a table of
+//! named clips, entities pick the clip that plays and the frames are advanced here.
+
+use bevy::prelude::*;
+
+use crate::GameState;
+
+pub struct AnimationPlugin;
+
+/// The clips a sheet can have, not every sheet has all of them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClipName {
+    Idle,
+    WalkLeft,
+    WalkRight,
+    WalkUp,
+    WalkDown,
+    Hurt,
+    Death,
+    /// Turning into a cactus, then staying one
+    Disguise,
+    Spin,
+}
+
+impl ClipName {
+    /// The walk clip for moving in this direction
+    pub fn walk(direction: Vec2) -> Self {
+        if direction.x.abs() >= direction.y.abs() {
+            if direction.x < 0. {
+                ClipName::WalkLeft
+            } else {
+                ClipName::WalkRight
+            }
+        } else if direction.y < 0. {
+            ClipName::WalkDown
+        } else {
+            ClipName::WalkUp
+        }
+    }
+}
+
+/// Frames of a sheet shown one after another
+pub struct Clip {
+    pub name: ClipName,
+    /// Indices into the atlas
+    pub frames: &'static [usize],
+    pub fps: f32,
+    pub looping: bool,
+    /// Mirrors the frames, so one set of frames can face both ways
+    pub flip_x: bool,
+}
+
+impl Clip {
+    const fn new(name: ClipName, frames: &'static [usize], fps: f32) -> Self {
+        Clip {
+            name,
+            frames,
+            fps,
+            looping: true,
+            flip_x: false,
+        }
+    }
+
+    const fn once(mut self) -> Self {
+        self.looping = false;
+        self
+    }
+
+    const fn flipped(mut self) -> Self {
+        self.flip_x = true;
+        self
+    }
+}
+
+/// The clips of `ninja_sheet.png` and `monk_sheet.png`, which share their layout
+pub const CHARACTER_CLIPS: &[Clip] = &[
+    Clip::new(ClipName::Idle, &[1], 1.),
+    Clip::new(ClipName::WalkRight, &[1, 2], 6.),
+    Clip::new(ClipName::WalkLeft, &[1, 2], 6.).flipped(),
+    Clip::new(ClipName::WalkUp, &[1, 2], 6.),
+    Clip::new(ClipName::WalkDown, &[1, 2], 6.),
+    Clip::new(ClipName::Hurt, &[3, 1, 3], 12.).once(),
+    Clip::new(ClipName::Death, &[4, 5, 6, 7], 6.).once(),
+    Clip::new(ClipName::Disguise, &[8, 9, 0], 8.).once(),
+];
+
+/// The clips of `shuriken.png`
+pub const SHURIKEN_CLIPS: &[Clip] = &[Clip::new(ClipName::Spin, &[0, 1], 10.)];
+
+/// Plays one clip at a time from a table of clips
+#[derive(Component)]
+pub struct SpriteAnimation {
+    clips: &'static [Clip],
+    /// Index into `clips`
+    clip: usize,
+    /// Index into the frames of the clip
+    frame: usize,
+    timer: Timer,
+    finished: bool,
+}
+
+impl SpriteAnimation {
+    pub fn new(clips: &'static [Clip], name: ClipName) -> Self {
+        let mut animation = SpriteAnimation {
+            clips,
+            clip: 0,
+            frame: 0,
+            timer: Timer::default(),
+            finished: false,
+        };
+        animation.start(name);
+        animation
+    }
+
+    fn start(&mut self, name: ClipName) {
+        let Some(index) = self.clips.iter().position(|clip| clip.name == name) else {
+            return;
+        };
+        self.clip = index;
+        self.frame = 0;
+        self.finished = false;
+        self.timer = Timer::from_seconds(1. / self.clips[index].fps, TimerMode::Repeating);
+    }
+
+    /// Switches to another clip from its first frame, the current clip just keeps going. Clips
+    /// the sheet doesn't have are ignored.
+    pub fn play(&mut self, name: ClipName) {
+        if self.current() != name {
+            self.start(name);
+        }
+    }
+
+    pub fn current(&self) -> ClipName {
+        self.clips[self.clip].name
+    }
+
+    /// Whether a clip that doesn't loop has shown its last frame for as long as the others
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn index(&self) -> usize {
+        self.clips[self.clip].frames[self.frame]
+    }
+}
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            animate_sprites.run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Runs after everything had the chance to pick a clip this frame
+fn animate_sprites(
+    time: Res<Time>,
+    mut animation_query: Query<(&mut SpriteAnimation, &mut TextureAtlas, &mut Sprite)>,
+) {
+    for (mut animation, mut atlas, mut sprite) in &mut animation_query {
+        let frames = animation.clips[animation.clip].frames.len();
+        let looping = animation.clips[animation.clip].looping;
+        let steps = animation
+            .timer
+            .tick(time.delta())
+            .times_finished_this_tick() as usize;
+        let frame = animation.frame + steps;
+        if looping {
+            animation.frame = frame % frames;
+        } else if frame >= frames {
+            animation.frame = frames - 1;
+            animation.finished = true;
+        } else {
+            animation.frame = frame;
+        }
+        let index = animation.index();
+        if atlas.index != index {
+            atlas.index = index;
+        }
+        let flip_x = animation.clips[animation.clip].flip_x;
+        if sprite.flip_x != flip_x {
+            sprite.flip_x = flip_x;
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+use crate::enemy::{EnemyDamaged, EnemyKilled};
+use crate::loading::AudioAssets;
+use crate::player::{Death, LevelUp};
+use crate::settings::Settings;
+use crate::GameState;
+
+// Seconds it takes for one music track to fade into the next
+const CROSSFADE_DURATION: f32 = 1.5;
+// A sound that hasn't started playing after this many seconds never will
+const START_TIMEOUT: f32 = 1.;
+
+pub struct InternalAudioPlugin;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sfx {
+    Throw,
+    Hit,
+    Kill,
+    LevelUp,
+    Explosion,
+    Death,
+}
+
+/// Request to play a sound effect once
+#[derive(Event)]
+pub struct PlaySfx(pub Sfx);
+
+#[derive(Component)]
+struct Music {
+    /// Current loudness relative to the music volume setting, between 0 and 1
+    level: f32,
+    fading_out: bool,
+}
+
+/// Whether there is anything to play sounds on. Bevy doesn't say, but without an audio device it
+/// never starts playing anything, so the first sound that doesn't start in time gives it away
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+enum AudioDevice {
+    #[default]
+    Unknown,
+    Available,
+    /// No more sounds are spawned, they would only pile up without ever playing
+    Missing,
+}
+
+/// A sound that was spawned but hasn't started playing yet
+#[derive(Component)]
+struct Starting(Timer);
+
+impl Starting {
+    fn new() -> Self {
+        Starting(Timer::from_seconds(START_TIMEOUT, TimerMode::Once))
+    }
+}
+
+impl Plugin for InternalAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaySfx>()
+            .init_resource::<AudioDevice>()
+            .add_systems(OnEnter(GameState::Menu), start_menu_music)
+            .add_systems(OnEnter(GameState::Playing), start_gameplay_music)
+            .add_systems(
+                Update,
+                (sfx_from_gameplay_events, play_sfx)
+                    .chain()
+                    .run_if(not(in_state(GameState::Loading))),
+            )
+            .add_systems(
+                Update,
+                (fade_music, check_audio_device).run_if(not(in_state(GameState::Loading))),
+            );
+    }
+}
+
+/// Gives up on sounds that never start, and on every sound after that once there is no device
+fn check_audio_device(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut device: ResMut<AudioDevice>,
+    mut sound_query: Query<(Entity, &mut Starting, Has<AudioSink>)>,
+) {
+    for (entity, mut starting, playing) in &mut sound_query {
+        if playing {
+            *device = AudioDevice::Available;
+            commands.entity(entity).remove::<Starting>();
+        } else if starting.0.tick(time.delta()).finished() {
+            if *device == AudioDevice::Unknown {
+                warn!("Sounds don't start playing, continuing without audio");
+                *device = AudioDevice::Missing;
+            }
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn start_music(
+    commands: &mut Commands,
+    device: AudioDevice,
+    track: Handle<AudioSource>,
+    music_query: &mut Query<&mut Music>,
+) {
+    for mut music in music_query.iter_mut() {
+        music.fading_out = true;
+    }
+    if device == AudioDevice::Missing {
+        return;
+    }
+    commands.spawn((
+        AudioBundle {
+            source: track,
+            settings: PlaybackSettings::LOOP.with_volume(Volume::new(0.)),
+        },
+        Music {
+            level: 0.,
+            fading_out: false,
+        },
+        Starting::new(),
+    ));
+}
+
+fn start_menu_music(
+    mut commands: Commands,
+    device: Res<AudioDevice>,
+    audio: Res<AudioAssets>,
+    mut music_query: Query<&mut Music>,
+) {
+    start_music(
+        &mut commands,
+        *device,
+        audio.menu_music.clone(),
+        &mut music_query,
+    );
+}
+
+fn start_gameplay_music(
+    mut commands: Commands,
+    device: Res<AudioDevice>,
+    audio: Res<AudioAssets>,
+    mut music_query: Query<&mut Music>,
+) {
+    start_music(
+        &mut commands,
+        *device,
+        audio.gameplay_music.clone(),
+        &mut music_query,
+    );
+}
+
+fn fade_music(
+    time: Res<Time>,
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut music_query: Query<(Entity, &mut Music, Option<&AudioSink>)>,
+) {
+    let step = time.delta_seconds() / CROSSFADE_DURATION;
+    for (entity, mut music, sink) in &mut music_query {
+        if music.fading_out {
+            music.level = (music.level - step).max(0.);
+            if music.level == 0. {
+                commands.entity(entity).despawn_recursive();
+                continue;
+            }
+        } else {
+            music.level = (music.level + step).min(1.);
+        }
+        // The sink only shows up once the track has started playing
+        if let Some(sink) = sink {
+            sink.set_volume(music.level * settings.master_volume * settings.music_volume);
+        }
+    }
+}
+
+fn sfx_from_gameplay_events(
+    mut damaged_events: EventReader<EnemyDamaged>,
+    mut killed_events: EventReader<EnemyKilled>,
+    mut level_up_events: EventReader<LevelUp>,
+    mut death_events: EventReader<Death>,
+    mut sfx_events: EventWriter<PlaySfx>,
+) {
+    for _ in damaged_events.read() {
+        sfx_events.send(PlaySfx(Sfx::Hit));
+    }
+    for _ in killed_events.read() {
+        sfx_events.send(PlaySfx(Sfx::Kill));
+    }
+    for _ in level_up_events.read() {
+        sfx_events.send(PlaySfx(Sfx::LevelUp));
+    }
+    for _ in death_events.read() {
+        sfx_events.send(PlaySfx(Sfx::Death));
+    }
+}
+
+fn play_sfx(
+    mut commands: Commands,
+    device: Res<AudioDevice>,
+    audio: Res<AudioAssets>,
+    settings: Res<Settings>,
+    mut sfx_events: EventReader<PlaySfx>,
+) {
+    if *device == AudioDevice::Missing {
+        sfx_events.clear();
+        return;
+    }
+    let volume = settings.master_volume * settings.sfx_volume;
+    // Many hits can land in the same frame, playing them all at once just makes it louder
+    let mut played = Vec::new();
+    for PlaySfx(sfx) in sfx_events.read() {
+        if played.contains(sfx) || volume <= 0. {
+            continue;
+        }
+        played.push(*sfx);
+        let source = match sfx {
+            Sfx::Throw => audio.throw.clone(),
+            Sfx::Hit => audio.hit.clone(),
+            Sfx::Kill => audio.kill.clone(),
+            Sfx::LevelUp => audio.level_up.clone(),
+            Sfx::Explosion => audio.explosion.clone(),
+            Sfx::Death => audio.death.clone(),
+        };
+        commands.spawn((
+            AudioBundle {
+                source,
+                settings: PlaybackSettings::DESPAWN.with_volume(Volume::new(volume)),
+            },
+            Starting::new(),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    // Without the audio plugin nothing ever starts playing, just like without an audio device
+    fn app_without_audio_device() -> App {
+        let mut app = App::new();
+        app.add_event::<PlaySfx>()
+            .init_resource::<AudioDevice>()
+            .init_resource::<Time>()
+            .insert_resource(Settings::default())
+            .insert_resource(AudioAssets {
+                throw: default(),
+                hit: default(),
+                kill: default(),
+                level_up: default(),
+                explosion: default(),
+                death: default(),
+                menu_music: default(),
+                gameplay_music: default(),
+            })
+            .add_systems(Update, (play_sfx, check_audio_device).chain());
+        app
+    }
+
+    fn sounds(app: &mut App) -> usize {
+        app.world
+            .query_filtered::<(), With<Handle<AudioSource>>>()
+            .iter(&app.world)
+            .count()
+    }
+
+    #[test]
+    fn gives_up_on_sounds_without_an_audio_device() {
+        let mut app = app_without_audio_device();
+        app.world.send_event(PlaySfx(Sfx::Throw));
+        app.update();
+        assert_eq!(sounds(&mut app), 1);
+        assert_eq!(*app.world.resource::<AudioDevice>(), AudioDevice::Unknown);
+
+        app.world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(START_TIMEOUT));
+        app.update();
+        assert_eq!(sounds(&mut app), 0);
+        assert_eq!(*app.world.resource::<AudioDevice>(), AudioDevice::Missing);
+
+        // Nothing piles up from then on
+        app.world.send_event(PlaySfx(Sfx::Hit));
+        app.update();
+        assert_eq!(sounds(&mut app), 0);
+    }
+}
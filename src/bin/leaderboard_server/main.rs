@@ -0,0 +1,19 @@
+//! A small leaderboard server for developing the `online` feature without a real backend.
+//! It keeps every run in memory, so restarting it starts from an empty leaderboard.
+//!
+//! `cargo run --bin leaderboard_server --features online [address]`, the address defaults to
+//! 127.0.0.1:8080 which is also where the game looks unless `LEADERBOARD_URL` says otherwise.
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    server::run();
+}
+
+// tiny_http needs real sockets, which a browser doesn't have
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    panic!("The leaderboard server only runs natively");
+}
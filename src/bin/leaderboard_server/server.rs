@@ -0,0 +1,159 @@
+use std::cmp::Ordering;
+
+use ninja_killers_10::Submission;
+use serde_json::Value;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 100;
+
+/// Serves requests until the process is stopped
+pub fn run() {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+    let server = Server::http(&address).expect("Failed to start the leaderboard server");
+    println!("Leaderboard server listening on http://{address}");
+
+    let mut runs: Vec<Value> = Vec::new();
+    for mut request in server.incoming_requests() {
+        let (path, query) = match request.url().split_once('?') {
+            Some((path, query)) => (path.to_string(), query.to_string()),
+            None => (request.url().to_string(), String::new()),
+        };
+        let response = match (request.method(), path.as_str()) {
+            (Method::Post, "/scores") => {
+                let mut body = String::new();
+                match request.as_reader().read_to_string(&mut body) {
+                    Ok(_) => submit(&mut runs, &body),
+                    Err(error) => (400, format!("Unreadable body: {error}")),
+                }
+            }
+            (Method::Get, "/scores") => (200, top(&runs, &query)),
+            _ => (404, "Not found".to_string()),
+        };
+        respond(request, response);
+    }
+}
+
+fn respond(request: Request, (status, body): (u16, String)) {
+    let content_type = if status == 200 {
+        "application/json"
+    } else {
+        "text/plain"
+    };
+    let header = Header::from_bytes("Content-Type", content_type).unwrap();
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header);
+    if let Err(error) = request.respond(response) {
+        eprintln!("Failed to respond: {error}");
+    }
+}
+
+/// Reads a submission the way the game does before keeping its entry, so every entry it serves
+/// can be read back by the game
+fn submit(runs: &mut Vec<Value>, body: &str) -> (u16, String) {
+    let submission: Submission = match serde_json::from_str(body) {
+        Ok(submission) => submission,
+        Err(error) => return (400, format!("Invalid submission: {error}")),
+    };
+    println!(
+        "{} scored {} (replay {})",
+        submission.entry.name, submission.entry.score, submission.replay_hash
+    );
+    match serde_json::to_value(&submission.entry) {
+        Ok(entry) => runs.push(entry),
+        Err(error) => return (500, format!("Failed to keep the entry: {error}")),
+    }
+    (201, String::new())
+}
+
+/// The best entries of the table picked by the `difficulty` and `arena` parameters
+fn top(runs: &[Value], query: &str) -> String {
+    let parameter = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.to_string())
+    };
+    let difficulty = parameter("difficulty");
+    let arena = parameter("arena");
+    let limit = parameter("limit")
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(DEFAULT_LIMIT)
+        .min(MAX_LIMIT);
+
+    let matches = |run: &&Value, field: &str, wanted: &Option<String>| {
+        wanted
+            .as_ref()
+            .map_or(true, |wanted| run[field].as_str() == Some(wanted.as_str()))
+    };
+    let mut table: Vec<&Value> = runs
+        .iter()
+        .filter(|run| matches(run, "difficulty", &difficulty) && matches(run, "arena", &arena))
+        .collect();
+    // The same order as the local leaderboard, the earlier run wins ties
+    table.sort_by(|a, b| {
+        let score = |run: &Value| run["score"].as_i64().unwrap_or(0);
+        let date = |run: &Value| run["date"].as_u64().unwrap_or(0);
+        match score(b).cmp(&score(a)) {
+            Ordering::Equal => date(a).cmp(&date(b)),
+            ordering => ordering,
+        }
+    });
+    table.truncate(limit);
+    Value::from(table.into_iter().cloned().collect::<Vec<_>>()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use ninja_killers_10::LeaderboardEntry;
+
+    use super::*;
+
+    fn submission(name: &str, score: i32) -> Value {
+        serde_json::json!({
+            "entry": {
+                "name": name,
+                "score": score,
+                "level": 3,
+                "survival_time": 42.5,
+                "kills": 12,
+                "date": 1_700_000_000,
+                "seed": 7,
+                "version": "0.2.0",
+                "difficulty": "Normal",
+                "arena": "Desert",
+            },
+            "replay_hash": "0123456789abcdef",
+        })
+    }
+
+    #[test]
+    fn bad_submissions_are_not_served() {
+        let mut runs = Vec::new();
+        let mut missing_kills = submission("Missing", 30);
+        missing_kills["entry"]
+            .as_object_mut()
+            .unwrap()
+            .remove("kills");
+        let mut unknown_difficulty = submission("Unknown", 20);
+        unknown_difficulty["entry"]["difficulty"] = "Foo".into();
+
+        assert_eq!(
+            submit(&mut runs, &submission("Good", 10).to_string()).0,
+            201
+        );
+        assert_eq!(submit(&mut runs, &missing_kills.to_string()).0, 400);
+        assert_eq!(submit(&mut runs, &unknown_difficulty.to_string()).0, 400);
+        assert_eq!(submit(&mut runs, "not json").0, 400);
+
+        let top: Vec<LeaderboardEntry> =
+            serde_json::from_str(&top(&runs, "difficulty=Normal&arena=Desert")).unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].name, "Good");
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::animation::{ClipName, SpriteAnimation, CHARACTER_CLIPS};
+use crate::dash::DashStats;
+use crate::data::{DataAsset, DataAssetApp};
+use crate::enemy::EnemyKilled;
+use crate::item::Weapon;
+use crate::loading::{DataAssets, TextureAssets};
+use crate::player::Player;
+use crate::settings::Settings;
+use crate::status::{StatusEffect, StatusEffects, StatusKind};
+use crate::GameState;
+
+// How much faster the frenzy passive makes the player
+const FRENZY_HASTE: f32 = 0.3;
+
+pub struct CharacterPlugin;
+
+/// One of the loaded sprite sheets a character can be drawn with, they all have the layout of
+/// [`CHARACTER_CLIPS`]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CharacterSheet {
+    Ninja,
+    Monk,
+}
+
+impl CharacterSheet {
+    pub fn image(&self, textures: &TextureAssets) -> Handle<Image> {
+        match self {
+            CharacterSheet::Ninja => textures.ninja_sheet.clone(),
+            CharacterSheet::Monk => textures.monk_sheet.clone(),
+        }
+    }
+
+    /// A sprite with the animations of the sheet, standing still to begin with
+    pub fn sprite(&self, textures: &TextureAssets, transform: Transform) -> impl Bundle {
+        (
+            SpriteSheetBundle {
+                transform,
+                texture: self.image(textures),
+                atlas: TextureAtlas {
+                    layout: textures.character_layout.clone(),
+                    index: 0,
+                },
+                ..default()
+            },
+            SpriteAnimation::new(CHARACTER_CLIPS, ClipName::Idle),
+        )
+    }
+}
+
+/// What the player starts every run with before upgrades are applied
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct BaseStats {
+    pub max_health: f32,
+    /// Multiplies the movement speed
+    pub move_speed: f32,
+    /// Multiplies the experience from orbs
+    pub experience_gain: f32,
+    pub magnet_range: f32,
+}
+
+impl Default for BaseStats {
+    fn default() -> Self {
+        BaseStats {
+            max_health: 1.,
+            move_speed: 1.,
+            experience_gain: 1.,
+            magnet_range: 50.,
+        }
+    }
+}
+
+/// Something a character is always doing in the background
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Passive {
+    /// Heals this much health per second
+    Regeneration(f32),
+    /// Gains a shield every this many seconds, kept until the next one
+    Barrier(f32),
+    /// Moves faster for this many seconds after every kill
+    Frenzy(f32),
+}
+
+impl Passive {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Passive::Regeneration(_) => "Regeneration",
+            Passive::Barrier(_) => "Barrier",
+            Passive::Frenzy(_) => "Frenzy",
+        }
+    }
+
+    pub fn amount(&self) -> f32 {
+        match self {
+            Passive::Regeneration(amount) | Passive::Barrier(amount) | Passive::Frenzy(amount) => {
+                *amount
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Character {
+    /// Stays the same when the name changes, leaderboard entries and settings refer to it
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub sprite_sheet: CharacterSheet,
+    #[serde(default)]
+    pub stats: BaseStats,
+    #[serde(default)]
+    pub dash: DashStats,
+    /// Thrown with the attack key, the other weapons keep their own keys
+    pub starting_weapon: Weapon,
+    pub passive: Option<Passive>,
+}
+
+/// Every playable character, from `assets/data/game.characters.toml`
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct CharacterList {
+    #[serde(rename = "character")]
+    pub characters: Vec<Character>,
+}
+
+impl CharacterList {
+    /// The character with this id, or the first one when it doesn't exist (anymore)
+    pub fn get_or_first(&self, id: &str) -> &Character {
+        self.characters
+            .iter()
+            .find(|character| character.id == id)
+            .unwrap_or(&self.characters[0])
+    }
+}
+
+impl DataAsset for CharacterList {
+    const EXTENSION: &'static str = "characters.toml";
+
+    fn validate(&self) -> Result<(), String> {
+        if self.characters.is_empty() {
+            return Err("there has to be at least one character".to_string());
+        }
+        let mut ids = HashSet::new();
+        for character in &self.characters {
+            if character.id.is_empty() || character.name.is_empty() {
+                return Err("every character needs an id and a name".to_string());
+            }
+            if !ids.insert(&character.id) {
+                return Err(format!("the character id {} is used twice", character.id));
+            }
+            let stats = character.stats;
+            if stats.max_health <= 0.
+                || stats.move_speed <= 0.
+                || stats.experience_gain < 0.
+                || stats.magnet_range < 0.
+            {
+                return Err(format!("{} has stats out of range", character.id));
+            }
+            if character.starting_weapon.is_evolution() {
+                return Err(format!(
+                    "{} can't start with {:?}, it only comes from evolving a weapon",
+                    character.id, character.starting_weapon
+                ));
+            }
+            if !character.dash.is_valid() {
+                return Err(format!("{} has a dash out of range", character.id));
+            }
+            if character
+                .passive
+                .is_some_and(|passive| passive.amount() <= 0.)
+            {
+                return Err(format!("the passive of {} needs an amount", character.id));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The passive of the player's character and when it triggers next
+#[derive(Component)]
+pub struct PassiveAbility {
+    pub passive: Passive,
+    timer: Timer,
+}
+
+impl PassiveAbility {
+    pub fn new(passive: Passive) -> Self {
+        let timer = match passive {
+            Passive::Barrier(every) => Timer::from_seconds(every, TimerMode::Repeating),
+            _ => Timer::default(),
+        };
+        PassiveAbility { passive, timer }
+    }
+}
+
+impl Plugin for CharacterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_data_asset::<CharacterList>()
+            .add_systems(Update, use_passives.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// The character picked for the next run
+pub fn selected_character<'a>(
+    settings: &Settings,
+    data: &DataAssets,
+    lists: &'a Assets<CharacterList>,
+) -> Option<&'a Character> {
+    lists
+        .get(&data.characters)
+        .map(|list| list.get_or_first(&settings.character))
+}
+
+fn use_passives(
+    time: Res<Time>,
+    mut player_query: Query<(&mut StatusEffects, &mut PassiveAbility), With<Player>>,
+    mut killed_events: EventReader<EnemyKilled>,
+) {
+    let kills = killed_events.read().count();
+    let Ok((mut statuses, mut ability)) = player_query.get_single_mut() else {
+        return;
+    };
+    match ability.passive {
+        // Part of the stats of the player
+        Passive::Regeneration(_) => {}
+        Passive::Barrier(every) => {
+            ability.timer.tick(time.delta());
+            // Lasts until the next one, so there is at most one shield at a time
+            if ability.timer.just_finished() {
+                statuses.apply(StatusEffect::new(StatusKind::Shield, 0., every));
+            }
+        }
+        Passive::Frenzy(duration) => {
+            if kills > 0 {
+                statuses.apply(StatusEffect::new(StatusKind::Haste, FRENZY_HASTE, duration));
+            }
+        }
+    }
+}
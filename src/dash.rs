@@ -0,0 +1,223 @@
+//! A quick burst of movement during which the player can't be hit. Every character has a few
+//! charges of it that come back one after another.
+
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use serde::Deserialize;
+
+use crate::actions::Actions;
+use crate::disguise::Disguise;
+use crate::player::{player_bounds, Player};
+use crate::GameState;
+
+// Seconds between the copies of the player left behind while dashing
+const AFTERIMAGE_INTERVAL: f32 = 0.03;
+// Seconds an afterimage takes to fade away
+const AFTERIMAGE_DURATION: f32 = 0.25;
+const AFTERIMAGE_ALPHA: f32 = 0.5;
+
+pub struct DashPlugin;
+
+/// How a character dashes
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct DashStats {
+    /// Pixels covered by one dash
+    pub distance: f32,
+    /// Seconds one dash takes
+    pub duration: f32,
+    /// Seconds from the start of a dash during which hits are ignored
+    pub invulnerability: f32,
+    /// Seconds until a used charge comes back
+    pub cooldown: f32,
+    /// Dashes that can be used back to back
+    pub charges: u32,
+}
+
+impl Default for DashStats {
+    fn default() -> Self {
+        DashStats {
+            distance: 160.,
+            duration: 0.15,
+            invulnerability: 0.3,
+            cooldown: 2.5,
+            charges: 2,
+        }
+    }
+}
+
+impl DashStats {
+    pub fn is_valid(&self) -> bool {
+        self.distance > 0.
+            && self.duration > 0.
+            && self.invulnerability >= 0.
+            && self.cooldown > 0.
+            && self.charges > 0
+    }
+}
+
+#[derive(Component)]
+pub struct Dash {
+    pub stats: DashStats,
+    /// Dashes that can be used right now
+    pub charges: u32,
+    /// Seconds until the next charge comes back
+    recharge: f32,
+    /// Direction and seconds left of the dash in progress
+    dashing: Option<(Vec2, f32)>,
+    afterimage: Timer,
+}
+
+impl Dash {
+    pub fn new(stats: DashStats) -> Self {
+        Dash {
+            stats,
+            charges: stats.charges,
+            recharge: stats.cooldown,
+            dashing: None,
+            afterimage: Timer::from_seconds(AFTERIMAGE_INTERVAL, TimerMode::Repeating),
+        }
+    }
+
+    pub fn is_dashing(&self) -> bool {
+        self.dashing.is_some()
+    }
+
+    /// How far along the next charge is between 0 and 1, 1 once every charge is back
+    pub fn recharge_progress(&self) -> f32 {
+        if self.charges >= self.stats.charges {
+            1.
+        } else {
+            1. - self.recharge / self.stats.cooldown
+        }
+    }
+}
+
+/// A fading copy of the player left behind by a dash
+#[derive(Component)]
+struct Afterimage(Timer);
+
+impl Plugin for DashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (start_dash, move_dashing, recharge_dash, fade_afterimages)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(OnExit(GameState::Playing), cleanup_afterimages);
+    }
+}
+
+fn start_dash(
+    actions: Res<Actions>,
+    mut player_query: Query<(&mut Player, &mut Dash, &mut Disguise)>,
+) {
+    if !actions.dash {
+        return;
+    }
+    let Ok((mut player, mut dash, mut disguise)) = player_query.get_single_mut() else {
+        return;
+    };
+    if player.health <= 0. || dash.is_dashing() || dash.charges == 0 {
+        return;
+    }
+    // Standing still dashes the way the player was last facing
+    let direction = actions.player_movement.unwrap_or(player.direction);
+    player.direction = direction;
+    player.invulnerable = player.invulnerable.max(dash.stats.invulnerability);
+    dash.charges -= 1;
+    dash.dashing = Some((direction, dash.stats.duration));
+    dash.afterimage.reset();
+    disguise.reveal();
+}
+
+/// Moves the player along the dash, stopping at the edge of the arena
+fn move_dashing(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<(
+        &mut Transform,
+        &mut Dash,
+        &Handle<Image>,
+        &TextureAtlas,
+        &Sprite,
+    )>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let Ok((mut transform, mut dash, image, atlas, sprite)) = player_query.get_single_mut() else {
+        return;
+    };
+    let Some((direction, left)) = dash.dashing else {
+        return;
+    };
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    let delta = time.delta_seconds().min(left);
+    let speed = dash.stats.distance / dash.stats.duration;
+    let bounds = player_bounds(window);
+    let position =
+        (transform.translation.xy() + direction * speed * delta).clamp(bounds.min, bounds.max);
+    transform.translation = position.extend(transform.translation.z);
+    dash.dashing = (left > delta).then_some((direction, left - delta));
+
+    if dash.afterimage.tick(time.delta()).just_finished() {
+        let mut afterimage_transform = *transform;
+        afterimage_transform.translation.z -= 0.1;
+        commands.spawn((
+            SpriteSheetBundle {
+                transform: afterimage_transform,
+                texture: image.clone(),
+                atlas: atlas.clone(),
+                sprite: Sprite {
+                    flip_x: sprite.flip_x,
+                    color: Color::rgba(1., 1., 1., AFTERIMAGE_ALPHA),
+                    ..default()
+                },
+                ..default()
+            },
+            Afterimage(Timer::from_seconds(AFTERIMAGE_DURATION, TimerMode::Once)),
+        ));
+    }
+}
+
+/// Brings back one charge at a time
+fn recharge_dash(time: Res<Time>, mut dash_query: Query<&mut Dash>) {
+    for mut dash in &mut dash_query {
+        if dash.charges >= dash.stats.charges {
+            dash.recharge = dash.stats.cooldown;
+            continue;
+        }
+        dash.recharge -= time.delta_seconds();
+        if dash.recharge <= 0. {
+            dash.charges += 1;
+            dash.recharge += dash.stats.cooldown;
+        }
+    }
+}
+
+fn fade_afterimages(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut afterimage_query: Query<(Entity, &mut Afterimage, &mut Sprite)>,
+) {
+    for (entity, mut afterimage, mut sprite) in &mut afterimage_query {
+        afterimage.0.tick(time.delta());
+        if afterimage.0.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        sprite
+            .color
+            .set_a(AFTERIMAGE_ALPHA * afterimage.0.fraction_remaining());
+    }
+}
+
+fn cleanup_afterimages(mut commands: Commands, afterimage_query: Query<Entity, With<Afterimage>>) {
+    for entity in &afterimage_query {
+        commands.entity(entity).despawn();
+    }
+}
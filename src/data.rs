@@ -0,0 +1,105 @@
+//! Game content that is declared in TOML files under `assets/` instead of in code. Every file is
+//! checked while it loads, so a mistake panics with the file and the reason before the menu
+//! shows up instead of halfway through a run.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use bevy::asset::{
+    io::Reader, AssetLoadFailedEvent, AssetLoader, AsyncReadExt, BoxedFuture, LoadContext,
+};
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+
+use crate::GameState;
+
+/// An asset read from a TOML file
+pub trait DataAsset: Asset + DeserializeOwned {
+    /// Bevy picks loaders by extension, so every kind of data gets its own, like
+    /// `achievements.toml` for `game.achievements.toml`
+    const EXTENSION: &'static str;
+
+    /// Checks what TOML can't, like ids being unique or referring to something that exists
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum DataError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataError::Io(error) => write!(f, "could not read the file: {error}"),
+            DataError::Toml(error) => write!(f, "invalid TOML: {error}"),
+            DataError::Invalid(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for DataError {}
+
+struct DataAssetLoader<T> {
+    extensions: [&'static str; 1],
+    asset: PhantomData<fn() -> T>,
+}
+
+impl<T: DataAsset> AssetLoader for DataAssetLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = DataError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<T, DataError>> {
+        Box::pin(async move {
+            let mut contents = String::new();
+            reader
+                .read_to_string(&mut contents)
+                .await
+                .map_err(DataError::Io)?;
+            let asset: T = toml::from_str(&contents).map_err(DataError::Toml)?;
+            asset.validate().map_err(DataError::Invalid)?;
+            Ok(asset)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
+
+pub trait DataAssetApp {
+    /// Registers a [`DataAsset`] and the loader for its files
+    fn add_data_asset<T: DataAsset>(&mut self) -> &mut Self;
+}
+
+impl DataAssetApp for App {
+    fn add_data_asset<T: DataAsset>(&mut self) -> &mut Self {
+        self.init_asset::<T>()
+            .register_asset_loader(DataAssetLoader::<T> {
+                extensions: [T::EXTENSION],
+                asset: PhantomData,
+            })
+            .add_systems(
+                Update,
+                stop_on_load_failure::<T>.run_if(in_state(GameState::Loading)),
+            )
+    }
+}
+
+/// The loading screen would wait forever for a file that failed, so the game panics with the
+/// reason instead
+fn stop_on_load_failure<T: DataAsset>(mut failed_events: EventReader<AssetLoadFailedEvent<T>>) {
+    if let Some(failed) = failed_events.read().next() {
+        panic!("{}", failed.error);
+    }
+}
@@ -0,0 +1,98 @@
+//! Standing still for a moment turns the player into a cactus. Enemies lose track of a disguised
+//! player and wander around, apart from the kinds that see through it.
+
+use bevy::prelude::*;
+
+use crate::actions::Actions;
+use crate::item::Projectile;
+use crate::GameState;
+
+// Seconds of standing still before the disguise is on
+const DISGUISE_DELAY: f32 = 0.5;
+// Seconds a full meter lasts
+pub const MAX_DISGUISE: f32 = 6.;
+// Seconds of disguise regained per second out of it
+const REFILL_RATE: f32 = 0.5;
+// Seconds before the disguise can be used again once attacking broke it or it ran out
+const DISGUISE_COOLDOWN: f32 = 3.;
+
+pub struct DisguisePlugin;
+
+#[derive(Component)]
+pub struct Disguise {
+    /// Enemies that can't see through the disguise have lost track of the player
+    pub active: bool,
+    /// Seconds of disguise left, drains while disguised and refills otherwise
+    pub meter: f32,
+    /// Seconds until the disguise can be used again
+    pub cooldown: f32,
+    /// Seconds the player has been standing still
+    still: f32,
+}
+
+impl Default for Disguise {
+    fn default() -> Self {
+        Disguise {
+            active: false,
+            meter: MAX_DISGUISE,
+            cooldown: 0.,
+            still: 0.,
+        }
+    }
+}
+
+impl Disguise {
+    /// Drops the disguise without a cooldown, as if the player had walked away
+    pub fn reveal(&mut self) {
+        self.active = false;
+        self.still = 0.;
+    }
+
+    fn break_off(&mut self) {
+        self.active = false;
+        self.cooldown = DISGUISE_COOLDOWN;
+        self.still = 0.;
+    }
+}
+
+impl Plugin for DisguisePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_disguise.run_if(in_state(GameState::Playing)));
+    }
+}
+
+fn update_disguise(
+    time: Res<Time>,
+    actions: Res<Actions>,
+    fired_query: Query<(), Added<Projectile>>,
+    mut disguise_query: Query<&mut Disguise>,
+) {
+    let Ok(mut disguise) = disguise_query.get_single_mut() else {
+        return;
+    };
+    let delta = time.delta_seconds();
+    let attacked = !fired_query.is_empty();
+    disguise.cooldown = (disguise.cooldown - delta).max(0.);
+    if actions.player_movement.is_some() {
+        // Walking away drops the disguise without a cooldown
+        disguise.reveal();
+    } else {
+        disguise.still += delta;
+    }
+
+    if disguise.active {
+        disguise.meter = (disguise.meter - delta).max(0.);
+        if attacked || disguise.meter <= 0. {
+            disguise.break_off();
+        }
+    } else {
+        disguise.meter = (disguise.meter + REFILL_RATE * delta).min(MAX_DISGUISE);
+        if disguise.still >= DISGUISE_DELAY
+            && disguise.cooldown <= 0.
+            && disguise.meter > 0.
+            && !attacked
+        {
+            disguise.active = true;
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::GameState;
+
+use super::{enemy_bounds, keep_in_bounds, move_enemy, Enemy};
+
+// Roughly the separation radius, so a neighbour lookup only has to visit the surrounding cells
+const GRID_CELL_SIZE: f32 = 48.;
+
+pub struct FlockingPlugin;
+
+/// Steering behaviours layered on top of the basic enemy movement
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Steering {
+    /// Enemies closer than this push away from each other
+    pub separation_radius: f32,
+    pub separation_weight: f32,
+    /// Enemies closer than this overlap and are pushed apart directly
+    pub min_distance: f32,
+    /// How much of the overlap is resolved each frame, between 0 and 1
+    pub push_strength: f32,
+    /// How quickly the direction turns towards the neighbours' heading, 0 disables alignment
+    pub alignment_weight: f32,
+}
+
+impl Default for Steering {
+    fn default() -> Self {
+        Steering {
+            separation_radius: 40.,
+            separation_weight: 60.,
+            min_distance: 24.,
+            push_strength: 0.5,
+            alignment_weight: 0.,
+        }
+    }
+}
+
+impl Steering {
+    /// Steering for enemies that hunt in packs and line up with their neighbours
+    pub fn pack() -> Self {
+        Steering {
+            alignment_weight: 2.,
+            ..Steering::default()
+        }
+    }
+}
+
+/// Spatial hash of the enemies, rebuilt every frame, so neighbours can be found without checking
+/// every pair of enemies
+#[derive(Resource, Default)]
+pub struct EnemyGrid {
+    cells: HashMap<IVec2, Vec<(Entity, Vec2, Vec2)>>,
+}
+
+impl EnemyGrid {
+    fn cell(position: Vec2) -> IVec2 {
+        (position / GRID_CELL_SIZE).floor().as_ivec2()
+    }
+
+    fn clear(&mut self) {
+        // Keep the allocated cells around, most of them are reused next frame
+        for entities in self.cells.values_mut() {
+            entities.clear();
+        }
+    }
+
+    fn insert(&mut self, entity: Entity, position: Vec2, direction: Vec2) {
+        self.cells
+            .entry(Self::cell(position))
+            .or_default()
+            .push((entity, position, direction));
+    }
+
+    /// All enemies within `radius` of `position`, as (entity, position, direction)
+    pub fn neighbours(
+        &self,
+        position: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = &(Entity, Vec2, Vec2)> {
+        let centre = Self::cell(position);
+        let reach = (radius / GRID_CELL_SIZE).ceil() as i32;
+        (-reach..=reach)
+            .flat_map(move |x| (-reach..=reach).map(move |y| centre + IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |(_, other, _)| other.distance_squared(position) <= radius * radius)
+    }
+}
+
+impl Plugin for FlockingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EnemyGrid>().add_systems(
+            Update,
+            (update_enemy_grid, steer_enemies)
+                .chain()
+                .after(move_enemy)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn update_enemy_grid(
+    mut grid: ResMut<EnemyGrid>,
+    enemy_query: Query<(Entity, &Transform, &Enemy)>,
+) {
+    grid.clear();
+    for (entity, transform, enemy) in &enemy_query {
+        grid.insert(entity, transform.translation.truncate(), enemy.direction);
+    }
+}
+
+fn steer_enemies(
+    time: Res<Time>,
+    grid: Res<EnemyGrid>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut enemy_query: Query<(Entity, &mut Transform, &mut Enemy, &Steering)>,
+) {
+    let window = window_query.get_single().unwrap();
+    let bounds = enemy_bounds(window);
+    for (entity, mut transform, mut enemy, steering) in &mut enemy_query {
+        let position = transform.translation.truncate();
+        let mut separation = Vec2::ZERO;
+        let mut push = Vec2::ZERO;
+        let mut heading = Vec2::ZERO;
+        for (other, other_position, other_direction) in
+            grid.neighbours(position, steering.separation_radius)
+        {
+            if *other == entity {
+                continue;
+            }
+            let offset = position - *other_position;
+            let distance = offset.length();
+            // Enemies spawned on exactly the same spot still need to be told apart
+            let away = offset.try_normalize().unwrap_or(if entity < *other {
+                Vec2::X
+            } else {
+                Vec2::NEG_X
+            });
+            separation += away * (1. - distance / steering.separation_radius);
+            if distance < steering.min_distance {
+                // Both enemies get pushed, so each only resolves half of the overlap
+                push += away * (steering.min_distance - distance) / 2.;
+            }
+            heading += *other_direction;
+        }
+
+        if steering.alignment_weight > 0. {
+            if let Some(heading) = heading.try_normalize() {
+                enemy.direction = enemy
+                    .direction
+                    .lerp(heading, steering.alignment_weight * time.delta_seconds())
+                    .try_normalize()
+                    .unwrap_or(enemy.direction);
+            }
+        }
+
+        let movement = separation * steering.separation_weight * time.delta_seconds()
+            + push * steering.push_strength;
+        let new_pos = keep_in_bounds(bounds, position, position + movement);
+        transform.translation.x = new_pos.x;
+        transform.translation.y = new_pos.y;
+    }
+}
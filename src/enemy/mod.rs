@@ -0,0 +1,262 @@
+use crate::animation::{ClipName, SpriteAnimation};
+use crate::disguise::Disguise;
+use crate::item::Weapon;
+use crate::menu::Score;
+use crate::player::Player;
+use crate::stats::{Stat, Stats};
+use crate::status::StatusEffects;
+use crate::GameState;
+use bevy::{prelude::*, window::PrimaryWindow};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use self::flocking::FlockingPlugin;
+pub use self::spawning::SpawnTelegraph;
+use self::spawning::SpawningPlugin;
+
+mod flocking;
+mod spawning;
+
+pub struct EnemyPlugin;
+
+const INITIAL_SPAWN_TIMER: f32 = 5.0;
+pub(super) const HALF_ENEMY_SIZE: f32 = 32.;
+
+/// Where enemies can go once they walked into view, the window minus half the size of an enemy
+pub fn enemy_bounds(window: &Window) -> Rect {
+    Rect::new(
+        -(window.width() / 2.0) + HALF_ENEMY_SIZE,
+        -(window.height() / 2.0) + HALF_ENEMY_SIZE,
+        window.width() / 2.0 - HALF_ENEMY_SIZE,
+        window.height() / 2.0 - HALF_ENEMY_SIZE,
+    )
+}
+
+/// Where an enemy moving from `from` to `to` ends up. Enemies inside the bounds can't leave
+/// them, the ones still walking in from outside the window aren't held back
+pub fn keep_in_bounds(bounds: Rect, from: Vec2, to: Vec2) -> Vec2 {
+    if bounds.contains(from) {
+        to.clamp(bounds.min, bounds.max)
+    } else {
+        to
+    }
+}
+
+// #[derive(Component)]
+// pub struct Collider;
+
+/// The kinds of enemies, told apart in the run statistics
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum EnemyKind {
+    Ninja,
+    /// Stronger than the player and hunts in packs
+    PackHunter,
+}
+
+impl EnemyKind {
+    pub const ALL: [EnemyKind; 2] = [EnemyKind::Ninja, EnemyKind::PackHunter];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnemyKind::Ninja => "Ninja",
+            EnemyKind::PackHunter => "Pack hunter",
+        }
+    }
+
+    /// Pack hunters follow the scent and aren't fooled by the cactus disguise
+    pub fn sees_through_disguise(&self) -> bool {
+        match self {
+            EnemyKind::Ninja => false,
+            EnemyKind::PackHunter => true,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Enemy {
+    pub kind: EnemyKind,
+    pub direction: Vec2,
+    pub health: f32,
+    // pub collider: Collider,
+    pub direction_timer: Timer,
+    /// The weapon that hit this enemy last gets the credit for killing it
+    pub last_hit_by: Option<Weapon>,
+}
+
+impl Enemy {
+    pub fn new(kind: EnemyKind, level: i32) -> Self {
+        let mut rng = rand::thread_rng();
+        Self {
+            kind,
+            direction: Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)).normalize(),
+            health: level.max(1) as f32,
+            // collider: Collider,
+            direction_timer: Timer::from_seconds(rng.gen_range(1.0..2.0), TimerMode::Repeating),
+            last_hit_by: None,
+        }
+    }
+}
+
+/// Sent whenever an enemy loses health
+#[derive(Event)]
+pub struct EnemyDamaged {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub amount: f32,
+}
+
+/// Sent when an enemy runs out of health, just before it is despawned
+#[derive(Event)]
+pub struct EnemyKilled {
+    pub position: Vec2,
+    pub score: i32,
+    pub experience: i32,
+    pub kind: EnemyKind,
+    /// `None` when it wasn't killed by a weapon, like running into the player's shield
+    pub weapon: Option<Weapon>,
+}
+
+#[derive(Resource)]
+pub struct SpawnTimer(pub Timer);
+
+impl SpawnTimer {
+    pub fn reset(&mut self) {
+        self.set(INITIAL_SPAWN_TIMER);
+    }
+    pub fn new(time: f32) -> Self {
+        Self(Timer::from_seconds(time, TimerMode::Repeating))
+    }
+    pub fn set(&mut self, time: f32) {
+        self.0 = Timer::from_seconds(time, TimerMode::Repeating);
+    }
+    pub fn scale(&mut self, factor: f32) {
+        self.set(self.0.duration().as_secs_f32() * factor);
+    }
+    pub fn halve(&mut self) {
+        debug!("Halving the spawn timer");
+        self.set(self.0.duration().as_secs_f32() / 2.);
+    }
+}
+
+#[derive(Resource)]
+pub struct SpawnTimerModifier(pub Timer);
+
+/// A new wave starts every time the enemies start spawning faster, the first one is 1
+#[derive(Resource)]
+pub struct Wave(pub u32);
+
+/// Enemy related stuff like movement
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SpawnTimer::new(INITIAL_SPAWN_TIMER))
+            .insert_resource(SpawnTimerModifier(Timer::from_seconds(
+                20.,
+                TimerMode::Repeating,
+            )))
+            .insert_resource(Wave(1))
+            .add_event::<EnemyDamaged>()
+            .add_event::<EnemyKilled>()
+            .add_systems(
+                Update,
+                (move_enemy, animate_enemies).run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, kill_enemies.run_if(in_state(GameState::Playing)))
+            .add_systems(OnEnter(GameState::Playing), reset_waves)
+            .add_systems(
+                Update,
+                update_spawn_timer.run_if(in_state(GameState::Playing)),
+            )
+            .add_plugins((SpawningPlugin, FlockingPlugin));
+    }
+}
+
+fn reset_waves(mut modify_timer: ResMut<SpawnTimerModifier>, mut wave: ResMut<Wave>) {
+    modify_timer.0.reset();
+    wave.0 = 1;
+}
+
+fn update_spawn_timer(
+    time: Res<Time>,
+    mut modify_timer: ResMut<SpawnTimerModifier>,
+    mut timer: ResMut<SpawnTimer>,
+    mut wave: ResMut<Wave>,
+) {
+    if modify_timer.0.tick(time.delta()).just_finished() {
+        timer.halve();
+        wave.0 += 1;
+    }
+}
+
+fn move_enemy(
+    time: Res<Time>,
+    mut enemy_query: Query<(&mut Transform, &mut Enemy, &StatusEffects, &Stats)>,
+    player_query: Query<(&Transform, &Disguise), (Without<Enemy>, With<Player>)>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let window = window_query.get_single().unwrap();
+    let bounds = enemy_bounds(window);
+    let (player_pos, disguise) = player_query.single();
+    for (mut enemy_transform, mut enemy, statuses, stats) in &mut enemy_query {
+        if !statuses.can_move() {
+            continue;
+        }
+        let position = enemy_transform.translation.truncate();
+        // Enemies arrive from outside the window and head straight in
+        if !bounds.contains(position) {
+            enemy.direction = (player_pos.translation.truncate() - position).normalize_or_zero();
+        }
+        let speed = stats.get(Stat::MoveSpeed);
+        let new_pos = keep_in_bounds(
+            bounds,
+            position,
+            position + enemy.direction * speed * time.delta_seconds(),
+        );
+        enemy_transform.translation = new_pos.extend(enemy_transform.translation.z);
+        enemy.direction_timer.tick(time.delta());
+        if enemy.direction_timer.finished() {
+            // Without a trail to follow they wander around
+            if disguise.active && !enemy.kind.sees_through_disguise() {
+                let mut rng = rand::thread_rng();
+                let new_direction =
+                    Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)).normalize();
+                enemy.direction = new_direction;
+            } else {
+                let new_direction = Vec2::new(
+                    player_pos.translation.x - enemy_transform.translation.x,
+                    player_pos.translation.y - enemy_transform.translation.y,
+                )
+                .normalize();
+                enemy.direction = new_direction;
+            };
+        }
+    }
+}
+
+/// Enemies walk the way they are heading
+fn animate_enemies(mut enemy_query: Query<(&Enemy, &mut SpriteAnimation)>) {
+    for (enemy, mut animation) in &mut enemy_query {
+        animation.play(ClipName::walk(enemy.direction));
+    }
+}
+
+fn kill_enemies(
+    mut commands: Commands,
+    enemy_query: Query<(Entity, &Transform, &Enemy)>,
+    mut score: ResMut<Score>,
+    mut killed_events: EventWriter<EnemyKilled>,
+) {
+    for (entity, transform, enemy) in &enemy_query {
+        if enemy.health <= 0. {
+            score.score += 1;
+            killed_events.send(EnemyKilled {
+                position: transform.translation.truncate(),
+                score: 1,
+                // TODO: Make monster have experience value
+                experience: 1,
+                kind: enemy.kind,
+                weapon: enemy.last_hit_by,
+            });
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
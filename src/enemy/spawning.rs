@@ -0,0 +1,265 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use rand::prelude::*;
+
+use crate::{
+    animation::{ClipName, SpriteAnimation, CHARACTER_CLIPS},
+    loading::TextureAssets,
+    player::Player,
+    run::Run,
+    stats::Stats,
+    status::StatusEffects,
+    GameState,
+};
+
+use super::{enemy_bounds, flocking::Steering, Enemy, EnemyKind, SpawnTimer, HALF_ENEMY_SIZE};
+
+// Enemies never appear closer to the player than this
+const MIN_PLAYER_DISTANCE: f32 = 250.;
+// How long the marker is shown before the enemy becomes active
+const TELEGRAPH_DURATION: f32 = 1.0;
+const TELEGRAPH_COLOR: Color = Color::rgba(1., 0.2, 0.2, 0.5);
+const MAX_ENEMIES: usize = 50;
+// Enemies arrive this far outside the window and walk in from there
+const SPAWN_MARGIN: f32 = 80.;
+const MARKER_SIZE: f32 = 12.;
+
+pub struct SpawningPlugin;
+
+/// The shape a group of enemies arrives in
+#[derive(Clone, Copy, Debug)]
+pub enum SpawnPattern {
+    Single,
+    /// A circle around the player
+    Ring,
+    /// A row along the edge of the window
+    Line,
+    /// A tight group at the edge of the window
+    Cluster,
+}
+
+impl SpawnPattern {
+    fn random(rng: &mut impl Rng) -> Self {
+        *[
+            (SpawnPattern::Single, 6),
+            (SpawnPattern::Ring, 1),
+            (SpawnPattern::Line, 2),
+            (SpawnPattern::Cluster, 2),
+        ]
+        .choose_weighted(rng, |(_, weight)| *weight)
+        .map(|(pattern, _)| pattern)
+        .unwrap_or(&SpawnPattern::Single)
+    }
+
+    /// Candidate positions for the pattern around the `edge` enemies arrive from, these still
+    /// have to be checked against the window and the player
+    fn positions(&self, rng: &mut impl Rng, edge: Rect, player: Vec2) -> Vec<Vec2> {
+        match self {
+            SpawnPattern::Single => vec![random_edge_point(rng, edge)],
+            SpawnPattern::Ring => {
+                let count = 8;
+                // Wide enough for the whole ring to be outside the window
+                let radius = [
+                    edge.min,
+                    edge.max,
+                    Vec2::new(edge.min.x, edge.max.y),
+                    Vec2::new(edge.max.x, edge.min.y),
+                ]
+                .into_iter()
+                .map(|corner| corner.distance(player))
+                .fold(MIN_PLAYER_DISTANCE, f32::max);
+                let offset = rng.gen_range(0.0..std::f32::consts::TAU);
+                (0..count)
+                    .map(|n| {
+                        let angle = offset + n as f32 * std::f32::consts::TAU / count as f32;
+                        player + Vec2::from_angle(angle) * radius
+                    })
+                    .collect()
+            }
+            SpawnPattern::Line => {
+                let count = 5;
+                let spacing = 40.;
+                let start = random_edge_point(rng, edge);
+                // Run along whichever edge the start point is on
+                let along = if start.x == edge.min.x || start.x == edge.max.x {
+                    Vec2::Y
+                } else {
+                    Vec2::X
+                };
+                let centre = (count - 1) as f32 * spacing / 2.;
+                (0..count)
+                    .map(|n| start + along * (n as f32 * spacing - centre))
+                    .collect()
+            }
+            SpawnPattern::Cluster => {
+                let anchor = random_edge_point(rng, edge);
+                (0..4)
+                    .map(|_| {
+                        anchor + Vec2::new(rng.gen_range(-40.0..40.), rng.gen_range(-40.0..40.))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Marks an enemy that is about to arrive. It waits outside the window and can't move, hurt or be
+/// hurt until the timer finishes
+#[derive(Component)]
+pub struct SpawnTelegraph {
+    pub timer: Timer,
+    kind: EnemyKind,
+    level: i32,
+    steering: Steering,
+}
+
+impl Plugin for SpawningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_enemy, activate_telegraphed_enemies).run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Shows where a telegraphed enemy is going to walk into the window
+#[derive(Component)]
+struct SpawnMarker;
+
+fn random_edge_point(rng: &mut impl Rng, edge: Rect) -> Vec2 {
+    let x = rng.gen_range(edge.min.x..edge.max.x);
+    let y = rng.gen_range(edge.min.y..edge.max.y);
+    match rng.gen_range(0..4) {
+        0 => Vec2::new(edge.min.x, y),
+        1 => Vec2::new(edge.max.x, y),
+        2 => Vec2::new(x, edge.min.y),
+        _ => Vec2::new(x, edge.max.y),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_enemy(
+    time: Res<Time>,
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    mut timer: ResMut<SpawnTimer>,
+    mut run: ResMut<Run>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    player_query: Query<(&Transform, &Player), Without<Enemy>>,
+    enemies_query: Query<(), Or<(With<Enemy>, With<SpawnTelegraph>)>>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let enemy_count = enemies_query.iter().count();
+    if enemy_count > MAX_ENEMIES {
+        return;
+    }
+
+    let window = window_query.get_single().unwrap();
+    let view = Vec2::new(window.width(), window.height());
+    // An enemy is out of sight once its centre is half its size outside the window
+    let hidden = Rect::from_center_size(Vec2::ZERO, view + 2. * HALF_ENEMY_SIZE);
+    let edge = Rect::from_center_size(Vec2::ZERO, view + 2. * SPAWN_MARGIN);
+    let bounds = enemy_bounds(window);
+    let (player_transform, player) = player_query.single();
+    let player_position = player_transform.translation.truncate();
+    let current_level = player.level;
+
+    let rng = &mut run.rng;
+    let new_enemy_level = rng.gen_range(current_level.value - 1..current_level.value + 3);
+    // Enemies stronger than the player hunt in packs
+    let (kind, steering) = if new_enemy_level > current_level.value {
+        (EnemyKind::PackHunter, Steering::pack())
+    } else {
+        (EnemyKind::Ninja, Steering::default())
+    };
+
+    let positions = SpawnPattern::random(rng)
+        .positions(rng, edge, player_position)
+        .into_iter()
+        // Nothing pops up in view, enemies walk in from outside the window
+        .filter(|position| !hidden.contains(*position))
+        .filter(|position| position.distance(player_position) >= MIN_PLAYER_DISTANCE)
+        .take(MAX_ENEMIES + 1 - enemy_count);
+    for position in positions {
+        // Where the enemy is going to walk in, relative to the scaled up enemy
+        let marker = (position.clamp(bounds.min, bounds.max) - position) / 2.;
+        commands
+            .spawn((
+                SpriteSheetBundle {
+                    transform: Transform::from_translation(position.extend(1.))
+                        .with_scale(Vec3::new(2., 2., 1.)),
+                    texture: textures.monk_sheet.clone(),
+                    atlas: TextureAtlas {
+                        layout: textures.character_layout.clone(),
+                        index: 1,
+                    },
+                    sprite: Sprite {
+                        color: TELEGRAPH_COLOR,
+                        ..default()
+                    },
+                    ..Default::default()
+                },
+                SpriteAnimation::new(CHARACTER_CLIPS, ClipName::WalkDown),
+                SpawnTelegraph {
+                    timer: Timer::from_seconds(TELEGRAPH_DURATION, TimerMode::Once),
+                    kind,
+                    level: new_enemy_level,
+                    steering,
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    SpriteBundle {
+                        transform: Transform::from_translation(marker.extend(0.)),
+                        sprite: Sprite {
+                            color: TELEGRAPH_COLOR,
+                            custom_size: Some(Vec2::splat(MARKER_SIZE / 2.)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    SpawnMarker,
+                ));
+            });
+    }
+}
+
+fn activate_telegraphed_enemies(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut telegraph_query: Query<(Entity, &mut SpawnTelegraph, &Children)>,
+    mut sprite_query: Query<&mut Sprite>,
+    marker_query: Query<(), With<SpawnMarker>>,
+) {
+    for (entity, mut telegraph, children) in &mut telegraph_query {
+        let finished = telegraph.timer.tick(time.delta()).finished();
+        // Blink faster as the enemy is about to arrive
+        let blink = (telegraph.timer.elapsed_secs() * 4.).powi(2).sin();
+        for child in children
+            .iter()
+            .filter(|child| marker_query.contains(**child))
+        {
+            if finished {
+                commands.entity(*child).despawn_recursive();
+            } else if let Ok(mut sprite) = sprite_query.get_mut(*child) {
+                sprite.color = TELEGRAPH_COLOR.with_a(0.3 + 0.7 * blink.abs());
+            }
+        }
+        let Ok(mut sprite) = sprite_query.get_mut(entity) else {
+            continue;
+        };
+        if finished {
+            sprite.color = Color::WHITE;
+            commands
+                .entity(entity)
+                .remove::<SpawnTelegraph>()
+                .insert(Enemy::new(telegraph.kind, telegraph.level))
+                .insert(Stats::for_enemy(telegraph.level))
+                .insert(telegraph.steering)
+                .insert(StatusEffects::default());
+        } else {
+            sprite.color = TELEGRAPH_COLOR.with_a(0.3 + 0.3 * blink.abs());
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::enemy::{EnemyDamaged, EnemyKilled};
+use crate::item::PassiveItemGained;
+use crate::loading::TextureAssets;
+use crate::menu::MainCamera;
+use crate::pickup::{ChestReward, PickupCollected};
+use crate::player::{Player, PlayerDamaged};
+use crate::settings::Settings;
+use crate::status::{tint_status_effects, StatusEffects};
+use crate::GameState;
+
+const FLOATING_TEXT_DURATION: f32 = 0.8;
+const FLOATING_TEXT_SPEED: f32 = 40.;
+const HIT_FLASH_DURATION: f32 = 0.1;
+// Sprite colours multiply the texture, so going above 1 washes it out towards white
+const HIT_FLASH_COLOR: Color = Color::rgb(4., 4., 4.);
+const DEATH_ANIMATION_DURATION: f32 = 0.4;
+const MAX_SHAKE_OFFSET: f32 = 12.;
+// How much trauma wears off per second
+const SHAKE_DECAY: f32 = 1.5;
+
+pub struct FeedbackPlugin;
+
+// Trauma from a hit that the player survives and from the one that defeats them
+const HIT_TRAUMA: f32 = 0.4;
+const DEFEAT_TRAUMA: f32 = 0.8;
+
+/// Shakes the camera while there is trauma left, stronger trauma shakes much harder
+#[derive(Resource, Default)]
+pub struct ScreenShake {
+    trauma: f32,
+    /// How far the shake currently moved the camera away from where it would be otherwise
+    offset: Vec2,
+}
+
+impl ScreenShake {
+    /// Adds trauma between 0 and 1, capped at 1 in total
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.);
+    }
+}
+
+#[derive(Component)]
+struct FloatingText {
+    timer: Timer,
+    color: Color,
+}
+
+#[derive(Component)]
+struct HitFlash(Timer);
+
+#[derive(Component)]
+struct DeathAnimation(Timer);
+
+impl Plugin for FeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenShake>()
+            .add_systems(
+                Update,
+                (
+                    spawn_damage_numbers,
+                    announce_pickups,
+                    announce_passive_items,
+                    start_hit_flash,
+                    update_hit_flash.after(tint_status_effects),
+                    spawn_death_animations,
+                    update_floating_text,
+                    update_death_animations,
+                    shake_on_player_damage,
+                    shake_camera.after(shake_on_player_damage),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), cleanup_feedback);
+    }
+}
+
+fn format_amount(amount: f32) -> String {
+    if (amount - amount.round()).abs() < 0.05 {
+        format!("{amount:.0}")
+    } else {
+        format!("{amount:.1}")
+    }
+}
+
+fn spawn_floating_text(commands: &mut Commands, position: Vec2, text: String, color: Color) {
+    let mut rng = rand::thread_rng();
+    // Spread the numbers out a little so simultaneous hits don't overlap
+    let jitter = Vec2::new(rng.gen_range(-8.0..8.0), rng.gen_range(0.0..8.0));
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                text,
+                TextStyle {
+                    font_size: 16.0,
+                    color,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_translation((position + jitter).extend(5.)),
+            ..default()
+        },
+        FloatingText {
+            timer: Timer::from_seconds(FLOATING_TEXT_DURATION, TimerMode::Once),
+            color,
+        },
+    ));
+}
+
+fn spawn_damage_numbers(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut damaged_events: EventReader<EnemyDamaged>,
+    mut killed_events: EventReader<EnemyKilled>,
+) {
+    if !settings.damage_numbers {
+        damaged_events.clear();
+        killed_events.clear();
+        return;
+    }
+    for damaged in damaged_events.read() {
+        spawn_floating_text(
+            &mut commands,
+            damaged.position,
+            format_amount(damaged.amount),
+            Color::rgb(0.9, 0.9, 0.9),
+        );
+    }
+    for killed in killed_events.read() {
+        spawn_floating_text(
+            &mut commands,
+            killed.position + Vec2::Y * 12.,
+            format!("+{}", killed.score),
+            Color::GOLD,
+        );
+    }
+}
+
+/// Names what was picked up, or what a chest gave
+fn announce_pickups(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut collected_events: EventReader<PickupCollected>,
+) {
+    let language = settings.language;
+    for collected in collected_events.read() {
+        let text = match collected.reward {
+            Some(ChestReward::Upgrade(upgrade)) => format!(
+                "{}: {}",
+                language.tr(upgrade.name()),
+                language.tr(upgrade.description())
+            ),
+            Some(ChestReward::Evolution(weapon)) => format!("{}!", language.tr(weapon.name())),
+            None => language.tr(collected.kind.name()).to_string(),
+        };
+        spawn_floating_text(
+            &mut commands,
+            collected.position + Vec2::Y * 16.,
+            text,
+            Color::rgb(0.5, 1., 0.5),
+        );
+    }
+}
+
+/// Names the passive item a level up gave and its new level
+fn announce_passive_items(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut gained_events: EventReader<PassiveItemGained>,
+) {
+    let language = settings.language;
+    for gained in gained_events.read() {
+        spawn_floating_text(
+            &mut commands,
+            gained.position + Vec2::Y * 28.,
+            format!(
+                "{} {}: {}",
+                language.tr(gained.item.name()),
+                gained.level,
+                language.tr(gained.item.description())
+            ),
+            Color::rgb(0.6, 0.8, 1.),
+        );
+    }
+}
+
+fn update_floating_text(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut text_query: Query<(Entity, &mut Transform, &mut Text, &mut FloatingText)>,
+) {
+    for (entity, mut transform, mut text, mut floating) in &mut text_query {
+        floating.timer.tick(time.delta());
+        transform.translation.y += FLOATING_TEXT_SPEED * time.delta_seconds();
+        let alpha = 1. - floating.timer.fraction();
+        for section in text.sections.iter_mut() {
+            section.style.color = floating.color.with_a(alpha);
+        }
+        if floating.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn start_hit_flash(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut damaged_events: EventReader<EnemyDamaged>,
+) {
+    for damaged in damaged_events.read() {
+        if settings.hit_flash {
+            commands
+                .entity(damaged.entity)
+                .try_insert(HitFlash(Timer::from_seconds(
+                    HIT_FLASH_DURATION,
+                    TimerMode::Once,
+                )));
+        }
+    }
+}
+
+fn update_hit_flash(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut commands: Commands,
+    mut flash_query: Query<(Entity, &mut HitFlash, &mut Sprite, Option<&StatusEffects>)>,
+) {
+    for (entity, mut flash, mut sprite, statuses) in &mut flash_query {
+        if flash.0.tick(time.delta()).finished() {
+            sprite.color = statuses
+                .and_then(|statuses| statuses.tint(settings.palette))
+                .unwrap_or(Color::WHITE);
+            commands.entity(entity).remove::<HitFlash>();
+        } else {
+            sprite.color = HIT_FLASH_COLOR;
+        }
+    }
+}
+
+fn spawn_death_animations(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    mut killed_events: EventReader<EnemyKilled>,
+) {
+    for killed in killed_events.read() {
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform::from_translation(killed.position.extend(1.))
+                    .with_scale(Vec3::new(2., 2., 1.)),
+                texture: textures.character.clone(),
+                ..default()
+            },
+            DeathAnimation(Timer::from_seconds(
+                DEATH_ANIMATION_DURATION,
+                TimerMode::Once,
+            )),
+        ));
+    }
+}
+
+fn update_death_animations(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut animation_query: Query<(Entity, &mut Transform, &mut Sprite, &mut DeathAnimation)>,
+) {
+    for (entity, mut transform, mut sprite, mut animation) in &mut animation_query {
+        animation.0.tick(time.delta());
+        let remaining = 1. - animation.0.fraction();
+        // Spin and shrink away while fading out
+        transform.rotate_z(10. * time.delta_seconds());
+        transform.scale = Vec3::new(2. * remaining, 2. * remaining, 1.);
+        sprite.color = Color::rgba(1., 0.3, 0.3, remaining);
+        if animation.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Every hit on the player shakes the camera, the last one the hardest
+fn shake_on_player_damage(
+    mut damaged_events: EventReader<PlayerDamaged>,
+    player_query: Query<&Player>,
+    mut shake: ResMut<ScreenShake>,
+) {
+    for _ in damaged_events.read() {
+        let defeated = player_query
+            .get_single()
+            .is_ok_and(|player| player.health <= 0.);
+        shake.add_trauma(if defeated { DEFEAT_TRAUMA } else { HIT_TRAUMA });
+    }
+}
+
+/// Moves the camera around where it would be without the shake, so it never fights whatever
+/// else places the camera
+fn shake_camera(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut shake: ResMut<ScreenShake>,
+    mut camera_query: Query<&mut Transform, With<MainCamera>>,
+) {
+    let Ok(mut camera_transform) = camera_query.get_single_mut() else {
+        return;
+    };
+    if !settings.screen_shake || shake.trauma <= 0. {
+        shake.trauma = 0.;
+        if shake.offset != Vec2::ZERO {
+            camera_transform.translation -= shake.offset.extend(0.);
+            shake.offset = Vec2::ZERO;
+        }
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    let strength = shake.trauma * shake.trauma * MAX_SHAKE_OFFSET;
+    let offset = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)) * strength;
+    camera_transform.translation += (offset - shake.offset).extend(0.);
+    shake.offset = offset;
+    shake.trauma = (shake.trauma - SHAKE_DECAY * time.delta_seconds()).max(0.);
+}
+
+fn cleanup_feedback(
+    mut commands: Commands,
+    mut shake: ResMut<ScreenShake>,
+    mut camera_query: Query<&mut Transform, With<MainCamera>>,
+    feedback_query: Query<Entity, Or<(With<FloatingText>, With<DeathAnimation>)>>,
+) {
+    shake.trauma = 0.;
+    if let Ok(mut camera_transform) = camera_query.get_single_mut() {
+        camera_transform.translation -= shake.offset.extend(0.);
+    }
+    shake.offset = Vec2::ZERO;
+    for entity in feedback_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,109 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::animation::{ClipName, SpriteAnimation, SHURIKEN_CLIPS};
+use crate::audio::{PlaySfx, Sfx};
+use crate::loading::TextureAssets;
+use crate::player::Player;
+use crate::stats::{Stat, Stats};
+use crate::status::{StatusEffect, StatusKind};
+use crate::GameState;
+
+use super::projectile::{OnHitEffect, Projectile};
+use super::{Arsenal, Damage, Weapon};
+
+// Distance of the blades from the player
+const ORBIT_RADIUS: f32 = 70.;
+// Radians per second
+const ORBIT_SPEED: f32 = 3.;
+
+pub struct BladeStormPlugin;
+
+/// Circles the player until the run is over
+#[derive(Component)]
+pub struct OrbitingBlade;
+
+impl Plugin for BladeStormPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_blades, orbit_blades).run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(OnExit(GameState::Playing), despawn_blades);
+    }
+}
+
+/// Blades join the storm one at a time without being thrown, until it is full
+fn spawn_blades(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    mut player_query: Query<(&Transform, &Player, &Stats, &mut Arsenal)>,
+    mut sfx_events: EventWriter<PlaySfx>,
+) {
+    let Ok((player_transform, player, stats, mut arsenal)) = player_query.get_single_mut() else {
+        return;
+    };
+    let Some(state) = arsenal.get_mut(Weapon::BladeStorm) else {
+        return;
+    };
+    if player.health <= 0. || !state.is_ready(player, stats) {
+        return;
+    }
+    state.thrown();
+    sfx_events.send(PlaySfx(Sfx::Throw));
+    let size = 1.5 * stats.get(Stat::Area);
+    commands.spawn((
+        SpriteSheetBundle {
+            transform: Transform::from_translation(
+                player_transform.translation.truncate().extend(0.),
+            )
+            .with_scale(Vec3::new(size, size, 1.)),
+            atlas: TextureAtlas {
+                layout: textures.shuriken_layout.clone(),
+                index: 0,
+            },
+            texture: textures.shuriken.clone(),
+            ..default()
+        },
+        SpriteAnimation::new(SHURIKEN_CLIPS, ClipName::Spin),
+        OrbitingBlade,
+        Damage,
+        Projectile::new(Weapon::BladeStorm, 1.5)
+            .with_pierce(u32::MAX)
+            .with_effect(OnHitEffect::Status(StatusEffect::new(
+                StatusKind::Slow,
+                0.3,
+                1.,
+            ))),
+    ));
+}
+
+/// Spreads the blades evenly around the player, however many there are
+fn orbit_blades(
+    time: Res<Time>,
+    player_query: Query<(&Transform, &Stats), (With<Player>, Without<OrbitingBlade>)>,
+    mut blade_query: Query<(Entity, &mut Transform), With<OrbitingBlade>>,
+) {
+    let Ok((player_transform, stats)) = player_query.get_single() else {
+        return;
+    };
+    let center = player_transform.translation.truncate();
+    let radius = ORBIT_RADIUS * stats.get(Stat::Area);
+    let mut blades: Vec<_> = blade_query.iter_mut().collect();
+    // Sorted so the blades keep their place in the circle from frame to frame
+    blades.sort_by_key(|(entity, _)| *entity);
+    let count = blades.len() as f32;
+    let turn = time.elapsed_seconds() * ORBIT_SPEED;
+    for (index, (_, mut transform)) in blades.into_iter().enumerate() {
+        let angle = turn + TAU * index as f32 / count;
+        let position = center + Vec2::from_angle(angle) * radius;
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
+
+fn despawn_blades(mut commands: Commands, blade_query: Query<Entity, With<OrbitingBlade>>) {
+    for entity in &blade_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
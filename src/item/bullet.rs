@@ -1,13 +1,15 @@
-use std::time::Duration;
-
 use crate::actions::Actions;
+use crate::animation::{ClipName, SpriteAnimation, SHURIKEN_CLIPS};
+use crate::audio::{PlaySfx, Sfx};
 use crate::loading::TextureAssets;
 use crate::player::Player;
+use crate::stats::{Stat, Stats};
+use crate::status::{StatusEffect, StatusKind};
 use crate::GameState;
 use bevy::prelude::*;
-use bevy::time::common_conditions::on_timer;
 
-use super::Damage;
+use super::projectile::{OnHitEffect, Projectile};
+use super::{Arsenal, Damage, Weapon};
 
 pub struct BulletPlugin;
 
@@ -16,34 +18,35 @@ pub struct Bullet {
     pub lifetime: f32,
     pub speed: f32,
     pub direction: Vec2,
-    pub animation_timer: AnimationTimer,
 }
 
-#[derive(Component, Deref, DerefMut)]
-pub struct AnimationTimer(Timer);
-
 /// Bullet related stuff like movement
 impl Plugin for BulletPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            spawn_bullet
-                .run_if(in_state(GameState::Playing))
-                .run_if(on_timer(Duration::from_millis(100))),
-        )
-        .add_systems(Update, move_bullet.run_if(in_state(GameState::Playing)));
+            (spawn_bullet, move_bullet).run_if(in_state(GameState::Playing)),
+        );
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_bullet(
     mut commands: Commands,
     textures: Res<TextureAssets>,
     actions: Res<Actions>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    player: Query<(&Transform, &Player), With<Player>>,
+    player: Query<(&Transform, &Player, &Stats), With<Player>>,
+    mut arsenal_query: Query<&mut Arsenal>,
     bullets_query: Query<&Damage>,
+    mut sfx_events: EventWriter<PlaySfx>,
 ) {
-    if !keyboard_input.pressed(KeyCode::Space) {
+    let mut arsenal = arsenal_query.single_mut();
+    let Some(state) = arsenal.get_mut(Weapon::Shuriken) else {
+        return;
+    };
+    let (player_transform, player, stats) = player.single();
+    if !state.is_ready(player, stats) || !Weapon::Shuriken.is_pressed(player, &keyboard_input) {
         return;
     }
     // Cap number of bullets at 100
@@ -51,23 +54,22 @@ fn spawn_bullet(
         return;
     }
 
-    let mut bullet_direction = if actions.player_movement.is_none() {
-        player.single().1.direction
-    } else {
-        actions.player_movement.unwrap()
-    };
-    let number_of_bullets = player.single().1.level.value / 5 + 1;
+    let mut bullet_direction = actions.player_movement.unwrap_or(player.direction);
+    let number_of_bullets = player.projectiles(Weapon::Shuriken, stats);
+    let size = 1.5 * stats.get(Stat::Area);
+    state.thrown();
+    sfx_events.send(PlaySfx(Sfx::Throw));
     for n in 1..number_of_bullets + 1 {
-        bullet_direction.x = bullet_direction.x * ((-1) ^ n) as f32;
-        bullet_direction.y = bullet_direction.y * ((-1) ^ (n + 1)) as f32;
+        bullet_direction.x *= ((-1) ^ n) as f32;
+        bullet_direction.y *= ((-1) ^ (n + 1)) as f32;
         commands
             .spawn(SpriteSheetBundle {
                 transform: Transform::from_translation(Vec3::new(
-                    player.single().0.translation.x,
-                    player.single().0.translation.y,
+                    player_transform.translation.x,
+                    player_transform.translation.y,
                     0.,
                 ))
-                .with_scale(Vec3::new(1.5, 1.5, 1.)),
+                .with_scale(Vec3::new(size, size, 1.)),
                 atlas: TextureAtlas {
                     layout: textures.shuriken_layout.clone(),
                     index: 0,
@@ -79,23 +81,29 @@ fn spawn_bullet(
                 lifetime: 10.,
                 speed: 100.,
                 direction: bullet_direction,
-                animation_timer: AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
             })
-            .insert(Damage);
+            .insert(SpriteAnimation::new(SHURIKEN_CLIPS, ClipName::Spin))
+            .insert(Damage)
+            .insert(
+                Projectile::new(Weapon::Shuriken, 1.)
+                    .with_pierce(2)
+                    .with_bounces(1)
+                    .with_effect(OnHitEffect::Status(StatusEffect::new(
+                        StatusKind::Slow,
+                        0.2,
+                        1.,
+                    ))),
+            );
     }
 }
 
 fn move_bullet(
     time: Res<Time>,
     mut commands: Commands,
-    mut bullet_query: Query<(&mut Transform, &mut Bullet, Entity, &mut TextureAtlas)>,
+    mut bullet_query: Query<(&mut Transform, &mut Bullet, Entity)>,
 ) {
-    for (mut bullet_transform, mut bullet, entity, mut sprite) in bullet_query.iter_mut() {
+    for (mut bullet_transform, mut bullet, entity) in bullet_query.iter_mut() {
         bullet.lifetime -= time.delta_seconds();
-        bullet.animation_timer.tick(time.delta());
-        if bullet.animation_timer.finished() {
-            sprite.index = (sprite.index + 1) % 2;
-        }
         let moving = bullet.direction.normalize() * bullet.speed * time.delta_seconds();
         bullet_transform.translation += Vec3::new(moving.x, moving.y, 0.);
         if bullet.lifetime <= 0. {
@@ -0,0 +1,152 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::character::CharacterList;
+use crate::data::DataAsset;
+use crate::loading::DataAssets;
+use crate::player::Player;
+
+use super::{Arsenal, PassiveItem, PassiveItems, Weapon, MAX_WEAPON_LEVEL};
+
+/// A weapon at its max level turns into another one with the next chest while the player owns
+/// the passive item
+#[derive(Deserialize, Debug)]
+pub struct Evolution {
+    pub weapon: Weapon,
+    pub passive: PassiveItem,
+    pub into: Weapon,
+}
+
+/// Every weapon evolution, from `assets/data/game.evolutions.toml`
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct EvolutionList {
+    #[serde(rename = "evolution")]
+    pub evolutions: Vec<Evolution>,
+}
+
+/// Sent when a chest evolves one of the player's weapons
+#[derive(Event, Default)]
+pub struct WeaponEvolved;
+
+impl EvolutionList {
+    /// Evolves the first weapon that is ready for it, returns what it evolved into
+    pub fn evolve(
+        &self,
+        player: &mut Player,
+        arsenal: &mut Arsenal,
+        items: &PassiveItems,
+    ) -> Option<&Evolution> {
+        let evolution = self.evolutions.iter().find(|evolution| {
+            arsenal.has(evolution.weapon)
+                && player.weapon_level_of(evolution.weapon) >= MAX_WEAPON_LEVEL
+                && items.level(evolution.passive) > 0
+        })?;
+        arsenal.evolve(evolution.weapon, evolution.into);
+        // It keeps the levels and the key of the weapon it came from
+        if player.starting_weapon == evolution.weapon {
+            player.starting_weapon = evolution.into;
+        }
+        Some(evolution)
+    }
+
+    /// Only starting weapons gain levels, so an evolution of any other weapon would never happen
+    pub fn check_weapons(&self, characters: &CharacterList) -> Result<(), String> {
+        for evolution in &self.evolutions {
+            if !characters
+                .characters
+                .iter()
+                .any(|character| character.starting_weapon == evolution.weapon)
+            {
+                return Err(format!(
+                    "{:?} never reaches level {MAX_WEAPON_LEVEL} to evolve, no character starts with it",
+                    evolution.weapon
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DataAsset for EvolutionList {
+    const EXTENSION: &'static str = "evolutions.toml";
+
+    fn validate(&self) -> Result<(), String> {
+        let mut weapons = HashSet::new();
+        let mut evolved = HashSet::new();
+        for evolution in &self.evolutions {
+            if evolution.weapon.is_evolution() {
+                return Err(format!(
+                    "{:?} is already evolved and can't evolve again",
+                    evolution.weapon
+                ));
+            }
+            if !evolution.into.is_evolution() {
+                return Err(format!(
+                    "{:?} can't evolve into {:?}, which isn't an evolved weapon",
+                    evolution.weapon, evolution.into
+                ));
+            }
+            if !weapons.insert(evolution.weapon) {
+                return Err(format!("{:?} has two evolutions", evolution.weapon));
+            }
+            if !evolved.insert(evolution.into) {
+                return Err(format!("two weapons evolve into {:?}", evolution.into));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks what each file can't on its own, once all of them are loaded
+pub(super) fn check_evolutions(
+    data: Res<DataAssets>,
+    evolutions: Res<Assets<EvolutionList>>,
+    characters: Res<Assets<CharacterList>>,
+) {
+    let (Some(evolutions), Some(characters)) = (
+        evolutions.get(&data.evolutions),
+        characters.get(&data.characters),
+    ) else {
+        return;
+    };
+    if let Err(message) = evolutions.check_weapons(characters) {
+        panic!("data/game.evolutions.toml: {message}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn characters(starting_weapons: &[&str]) -> CharacterList {
+        let characters: String = starting_weapons
+            .iter()
+            .enumerate()
+            .map(|(index, weapon)| {
+                format!(
+                    "[[character]]\nid = \"{index}\"\nname = \"{index}\"\ndescription = \"\"\n\
+                     sprite_sheet = \"ninja\"\nstarting_weapon = \"{weapon}\"\n"
+                )
+            })
+            .collect();
+        toml::from_str(&characters).unwrap()
+    }
+
+    #[test]
+    fn only_starting_weapons_can_evolve() {
+        let evolutions: EvolutionList = toml::from_str(
+            "[[evolution]]\nweapon = \"HomingShuriken\"\npassive = \"area\"\n\
+             into = \"ClusterMissiles\"\n",
+        )
+        .unwrap();
+        assert!(evolutions.validate().is_ok());
+        assert!(evolutions
+            .check_weapons(&characters(&["Shuriken", "HomingShuriken"]))
+            .is_ok());
+        assert!(evolutions
+            .check_weapons(&characters(&["Shuriken", "Grenade"]))
+            .is_err());
+    }
+}
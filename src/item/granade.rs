@@ -1,10 +1,20 @@
 use std::time::Duration;
 
-use bevy::{prelude::*, time::common_conditions::on_timer, transform::commands};
+use bevy::{prelude::*, time::common_conditions::on_timer};
 
-use crate::{actions::Actions, enemy::Enemy, loading::TextureAssets, player::Player, GameState};
+use crate::{
+    audio::{PlaySfx, Sfx},
+    enemy::Enemy,
+    feedback::ScreenShake,
+    loading::TextureAssets,
+    player::Player,
+    stats::{Stat, Stats},
+    status::{StatusEffect, StatusKind},
+    GameState,
+};
 
-use super::Damage;
+use super::projectile::{OnHitEffect, Projectile};
+use super::{Arsenal, Damage, Weapon};
 
 pub struct GranadePlugin;
 
@@ -23,11 +33,8 @@ impl Plugin for GranadePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            spawn_granade
-                .run_if(in_state(GameState::Playing))
-                .run_if(on_timer(Duration::from_millis(100))),
+            (spawn_granade, move_granade).run_if(in_state(GameState::Playing)),
         )
-        .add_systems(Update, move_granade.run_if(in_state(GameState::Playing)))
         .add_systems(
             Update,
             remove_explosions
@@ -47,23 +54,28 @@ fn spawn_granade(
     mut commands: Commands,
     textures: Res<TextureAssets>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    player: Query<(&Transform, &Player), With<Player>>,
-    granades_query: Query<&Damage>,
+    player: Query<(&Transform, &Player, &Stats), With<Player>>,
+    mut arsenal_query: Query<&mut Arsenal>,
+    mut sfx_events: EventWriter<PlaySfx>,
 ) {
-    if !keyboard_input.pressed(KeyCode::KeyR) {
+    let mut arsenal = arsenal_query.single_mut();
+    let Some(state) = arsenal.get_mut(Weapon::Grenade) else {
         return;
-    }
-    // Only allow 1 granade at a time
-    if granades_query.iter().count() > 0 {
+    };
+    let (player_transform, player, stats) = player.single();
+    // Only allow 1 granade at a time, or more when it is the starting weapon
+    if !state.is_ready(player, stats) || !Weapon::Grenade.is_pressed(player, &keyboard_input) {
         return;
     }
 
-    let player_transform = player.single().0;
+    let size = 0.5 * stats.get(Stat::Area);
+    state.thrown();
+    sfx_events.send(PlaySfx(Sfx::Throw));
 
     commands
         .spawn(SpriteBundle {
             transform: Transform::from_translation(player_transform.translation)
-                .with_scale(Vec3::new(0.5, 0.5, 1.)),
+                .with_scale(Vec3::new(size, size, 1.)),
             texture: textures.bevy.clone(),
             ..Default::default()
         })
@@ -72,7 +84,26 @@ fn spawn_granade(
             speed: 100.,
             lifetime: 5.,
         })
-        .insert(Damage);
+        .insert(Damage)
+        .insert(
+            Projectile::new(Weapon::Grenade, 1.)
+                .with_pierce(u32::MAX)
+                .with_effect(OnHitEffect::Status(StatusEffect::new(
+                    StatusKind::Freeze,
+                    0.,
+                    1.5,
+                )))
+                .with_effect(OnHitEffect::Status(StatusEffect::new(
+                    StatusKind::Burn,
+                    1.,
+                    3.,
+                )))
+                .with_effect(OnHitEffect::Status(StatusEffect::new(
+                    StatusKind::Vulnerability,
+                    0.5,
+                    3.,
+                ))),
+        );
 }
 
 fn move_granade(
@@ -81,6 +112,8 @@ fn move_granade(
     mut granade_query: Query<(&mut Transform, &mut Granade, Entity), With<Damage>>,
     enemies_query: Query<(&Transform, Entity, &Enemy), Without<Damage>>,
     textures: Res<TextureAssets>,
+    mut shake: ResMut<ScreenShake>,
+    mut sfx_events: EventWriter<PlaySfx>,
 ) {
     for (mut granade_transform, mut granade, granade_entity) in granade_query.iter_mut() {
         let direction = granade.target - granade_transform.translation;
@@ -95,6 +128,8 @@ fn move_granade(
                 ..Default::default()
             };
             commands.spawn(explosion_bundle).insert(Explosion);
+            shake.add_trauma(0.8);
+            sfx_events.send(PlaySfx(Sfx::Explosion));
         } else {
             granade_transform.translation += velocity;
         }
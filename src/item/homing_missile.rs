@@ -1,98 +1,185 @@
-use std::time::Duration;
+use bevy::prelude::*;
 
-use bevy::{prelude::*, time::common_conditions::on_timer};
+use crate::{
+    animation::{ClipName, SpriteAnimation, SHURIKEN_CLIPS},
+    audio::{PlaySfx, Sfx},
+    enemy::Enemy,
+    loading::TextureAssets,
+    player::Player,
+    stats::{Stat, Stats},
+    status::{StatusEffect, StatusKind},
+    GameState,
+};
 
-use crate::{enemy::Enemy, loading::TextureAssets, player::Player, GameState};
+use super::projectile::{OnHitEffect, Projectile};
+use super::{Arsenal, Damage, Weapon};
 
-use super::Damage;
+// How many smaller missiles a cluster missile bursts into
+const CLUSTER_SIZE: u32 = 3;
 
 #[derive(Component)]
 pub struct HomingMissile {
-    target: Vec3,
+    pub(super) target: Vec3,
     speed: f32,
-    pub animation_timer: HomingAnimationTimer,
     lifetime: f32,
+    /// How many smaller missiles this bursts into once it is used up
+    pub(super) burst: u32,
 }
 
-#[derive(Component, Deref, DerefMut)]
-pub struct HomingAnimationTimer(Timer);
+/// Sent when a cluster missile is used up on an enemy
+#[derive(Event)]
+pub(super) struct MissileBurst {
+    pub position: Vec2,
+    pub count: u32,
+}
 
 pub struct HomingMissilePlugin;
+
+/// A missile that flies towards where the target was when it was thrown
+fn missile(
+    textures: &TextureAssets,
+    weapon: Weapon,
+    position: Vec3,
+    target: Vec3,
+    size: f32,
+    burst: u32,
+) -> impl Bundle {
+    (
+        SpriteSheetBundle {
+            transform: Transform::from_translation(position).with_scale(Vec3::new(size, size, 1.)),
+            // sprite: TextureAtlasSprite::new(0),
+            atlas: TextureAtlas {
+                layout: textures.shuriken_layout.clone(),
+                index: 0,
+            },
+            texture: textures.shuriken.clone(),
+            ..Default::default()
+        },
+        HomingMissile {
+            target,
+            speed: 100.,
+            lifetime: 10.,
+            burst,
+        },
+        SpriteAnimation::new(SHURIKEN_CLIPS, ClipName::Spin),
+        Damage,
+        Projectile::new(weapon, 2.)
+            .with_effect(OnHitEffect::Knockback(30.))
+            .with_effect(OnHitEffect::Status(StatusEffect::new(
+                StatusKind::Stun,
+                0.,
+                0.3,
+            )))
+            .with_effect(OnHitEffect::Status(StatusEffect::new(
+                StatusKind::Poison,
+                0.5,
+                4.,
+            ))),
+    )
+}
+
+/// The closest enemies first, at most `count` of them
+fn closest_enemies(enemies: &Query<(&Transform, &Enemy)>, from: Vec3, count: usize) -> Vec<Vec3> {
+    let mut targets: Vec<Vec3> = enemies
+        .iter()
+        .filter(|(_, enemy)| enemy.health > 0.)
+        .map(|(transform, _)| transform.translation)
+        .collect();
+    targets.sort_by(|a, b| from.distance(*a).total_cmp(&from.distance(*b)));
+    targets.truncate(count);
+    targets
+}
+
 // Want to make a weapon which travells directly towards the nearest enemy and then follows it
 fn spawn_homing_missile(
     mut commands: Commands,
     textures: Res<TextureAssets>,
-    player_query: Query<&Transform, With<Player>>,
+    mut player_query: Query<(&Transform, &Player, &Stats, &mut Arsenal)>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
     enemies: Query<(&Transform, &Enemy)>,
     bullets: Query<&Damage>,
+    mut sfx_events: EventWriter<PlaySfx>,
 ) {
-    // Only spawn a missile if the user presses the E key and there are no more than 100 bullets
-    if !keyboard_input.pressed(KeyCode::KeyE) || bullets.iter().count() > 100 {
+    let (player_transform, player, stats, mut arsenal) = player_query.single_mut();
+    // Cluster missiles are thrown the same way once the homing shuriken evolved
+    let (weapon, burst) = if arsenal.has(Weapon::ClusterMissiles) {
+        (Weapon::ClusterMissiles, CLUSTER_SIZE)
+    } else {
+        (Weapon::HomingShuriken, 0)
+    };
+    let Some(state) = arsenal.get_mut(weapon) else {
+        return;
+    };
+    // Only spawn a missile if the user presses its key and there are no more than 100 bullets
+    if !state.is_ready(player, stats)
+        || !weapon.is_pressed(player, &keyboard_input)
+        || bullets.iter().count() > 100
+    {
         return;
     }
 
-    let player_transform = player_query.single();
-    let mut closest_enemy = None;
-    let mut closest_distance = f32::INFINITY;
-    for (enemy_transform, _) in enemies.iter() {
-        let distance = player_transform
-            .translation
-            .distance(enemy_transform.translation);
-        if distance < closest_distance {
-            closest_enemy = Some(enemy_transform);
-            closest_distance = distance;
-        }
+    // Every missile goes after a different enemy, the closest ones first
+    let targets = closest_enemies(
+        &enemies,
+        player_transform.translation,
+        player.projectiles(weapon, stats) as usize,
+    );
+    if !targets.is_empty() {
+        state.thrown();
+        sfx_events.send(PlaySfx(Sfx::Throw));
     }
-    if let Some(closest_enemy) = closest_enemy {
-        commands
-            .spawn(SpriteSheetBundle {
-                transform: Transform::from_translation(player_transform.translation)
-                    .with_scale(Vec3::new(1.5, 1.5, 1.)),
-                // sprite: TextureAtlasSprite::new(0),
-                atlas: TextureAtlas {
-                    layout: textures.shuriken_layout.clone(),
-                    index: 0,
-                },
-                texture: textures.shuriken.clone(),
-                ..Default::default()
-            })
-            .insert(HomingMissile {
-                target: closest_enemy.translation,
-                speed: 100.,
-                animation_timer: HomingAnimationTimer(Timer::from_seconds(
-                    0.1,
-                    TimerMode::Repeating,
-                )),
-                lifetime: 10.,
-            })
-            .insert(Damage);
+    let size = 1.5 * stats.get(Stat::Area);
+    for target in targets {
+        commands.spawn(missile(
+            &textures,
+            weapon,
+            player_transform.translation,
+            target,
+            size,
+            burst,
+        ));
+    }
+}
+
+/// Smaller missiles fly from where a cluster missile hit to the enemies closest to it
+fn burst_missiles(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    player_query: Query<&Stats, With<Player>>,
+    enemies: Query<(&Transform, &Enemy)>,
+    mut burst_events: EventReader<MissileBurst>,
+) {
+    let area = player_query
+        .get_single()
+        .map_or(1., |stats| stats.get(Stat::Area));
+    for burst in burst_events.read() {
+        let position = burst.position.extend(0.);
+        for target in closest_enemies(&enemies, position, burst.count as usize) {
+            commands.spawn(missile(
+                &textures,
+                Weapon::ClusterMissiles,
+                position,
+                target,
+                area,
+                0,
+            ));
+        }
     }
 }
 
 // Update the missile to move towards the target
 fn move_homing(
     time: Res<Time>,
-    mut homing_missile_query: Query<(
-        &mut Transform,
-        &mut HomingMissile,
-        Entity,
-        &mut TextureAtlas,
-    )>,
+    mut homing_missile_query: Query<(&mut Transform, &mut HomingMissile, Entity)>,
     mut commands: Commands,
 ) {
-    for (mut missile_transform, mut missile, entity, mut atlas) in homing_missile_query.iter_mut() {
+    for (mut missile_transform, mut missile, entity) in homing_missile_query.iter_mut() {
         // Adjust the missile's position towards the targets latest position
         let direction = missile.target - missile_transform.translation;
         let distance = direction.length();
         let velocity = direction.normalize() * missile.speed * time.delta_seconds();
 
         missile.lifetime -= time.delta_seconds();
-        missile.animation_timer.tick(time.delta());
-        // Update the missiles animation
-        if missile.animation_timer.finished() {
-            atlas.index = (atlas.index + 1) % 2;
-        }
 
         if distance < velocity.length() {
             missile_transform.translation = missile.target;
@@ -108,12 +195,10 @@ fn move_homing(
 
 impl Plugin for HomingMissilePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.add_event::<MissileBurst>().add_systems(
             Update,
-            spawn_homing_missile
-                .run_if(in_state(GameState::Playing))
-                .run_if(on_timer(Duration::from_millis(100))),
-        )
-        .add_systems(Update, move_homing.run_if(in_state(GameState::Playing)));
+            (spawn_homing_missile, move_homing, burst_missiles)
+                .run_if(in_state(GameState::Playing)),
+        );
     }
 }
@@ -1,20 +1,233 @@
+use std::time::Duration;
+
+use self::blade_storm::BladeStormPlugin;
 pub use self::bullet::Bullet;
 use self::bullet::BulletPlugin;
+pub use self::evolution::{EvolutionList, WeaponEvolved};
 use self::granade::GranadePlugin;
 use self::homing_missile::HomingMissilePlugin;
+use self::passive::PassiveItemPlugin;
+pub use self::passive::{PassiveItem, PassiveItemGained, PassiveItems};
+pub use self::projectile::Projectile;
+use self::projectile::ProjectilePlugin;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::data::DataAssetApp;
+use crate::player::Player;
+use crate::stats::{Stat, Stats};
+use crate::GameState;
 
+mod blade_storm;
 mod bullet;
+mod evolution;
 mod granade;
 mod homing_missile;
+mod passive;
+mod projectile;
+
+// The starting weapon can evolve once it has this level
+pub const MAX_WEAPON_LEVEL: i32 = 5;
 
 pub struct ItemPlugin;
 
 #[derive(Component)]
 pub struct Damage;
 
+/// What fired a projectile, so hits and kills can be credited to it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Weapon {
+    Shuriken,
+    HomingShuriken,
+    Grenade,
+    /// Evolved shuriken that keep circling the player
+    BladeStorm,
+    /// Evolved homing shuriken that burst into smaller ones
+    ClusterMissiles,
+}
+
+impl Weapon {
+    pub const ALL: [Weapon; 5] = [
+        Weapon::Shuriken,
+        Weapon::HomingShuriken,
+        Weapon::Grenade,
+        Weapon::BladeStorm,
+        Weapon::ClusterMissiles,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Weapon::Shuriken => "Shuriken",
+            Weapon::HomingShuriken => "Homing shuriken",
+            Weapon::Grenade => "Grenade",
+            Weapon::BladeStorm => "Blade storm",
+            Weapon::ClusterMissiles => "Cluster missiles",
+        }
+    }
+
+    /// Evolutions only come from a chest, nobody starts with them
+    pub fn is_evolution(&self) -> bool {
+        matches!(self, Weapon::BladeStorm | Weapon::ClusterMissiles)
+    }
+
+    /// Seconds between two throws
+    fn cooldown(&self) -> f32 {
+        match self {
+            Weapon::Shuriken
+            | Weapon::HomingShuriken
+            | Weapon::Grenade
+            | Weapon::ClusterMissiles => 0.1,
+            // Between two blades joining the storm
+            Weapon::BladeStorm => 0.5,
+        }
+    }
+
+    /// How many can be in the air at once, when that is few enough to show on the weapon bar
+    pub fn charges(&self, player: &Player, stats: &Stats) -> Option<usize> {
+        match self {
+            Weapon::Grenade | Weapon::BladeStorm => Some(player.projectiles(*self, stats) as usize),
+            Weapon::Shuriken | Weapon::HomingShuriken | Weapon::ClusterMissiles => None,
+        }
+    }
+
+    /// Its picture in the icon sheet
+    pub fn icon(&self) -> usize {
+        match self {
+            Weapon::Shuriken => 88,
+            Weapon::HomingShuriken => 102,
+            Weapon::Grenade => 172,
+            Weapon::BladeStorm => 94,
+            Weapon::ClusterMissiles => 105,
+        }
+    }
+
+    /// The key that throws it when it isn't the starting weapon, the shuriken only comes with
+    /// the characters that start with it and the blade storm needs no throwing
+    fn key(&self) -> Option<KeyCode> {
+        match self {
+            Weapon::Shuriken | Weapon::BladeStorm => None,
+            Weapon::HomingShuriken | Weapon::ClusterMissiles => Some(KeyCode::KeyE),
+            Weapon::Grenade => Some(KeyCode::KeyR),
+        }
+    }
+
+    /// Whether the player is trying to throw it, the starting weapon is thrown with Space
+    fn is_pressed(&self, player: &Player, keyboard_input: &ButtonInput<KeyCode>) -> bool {
+        if player.starting_weapon == *self {
+            keyboard_input.pressed(KeyCode::Space)
+        } else {
+            self.key().is_some_and(|key| keyboard_input.pressed(key))
+        }
+    }
+}
+
+/// A weapon the player carries
+pub struct WeaponState {
+    pub weapon: Weapon,
+    /// Finished once the weapon can be thrown again
+    pub cooldown: Timer,
+    /// How many thrown ones are still around
+    pub in_flight: usize,
+}
+
+impl WeaponState {
+    fn new(weapon: Weapon) -> Self {
+        let mut cooldown = Timer::from_seconds(weapon.cooldown(), TimerMode::Once);
+        cooldown.tick(cooldown.duration());
+        WeaponState {
+            weapon,
+            cooldown,
+            in_flight: 0,
+        }
+    }
+
+    /// Whether it is off cooldown and has charges left
+    fn is_ready(&self, player: &Player, stats: &Stats) -> bool {
+        self.cooldown.finished()
+            && self
+                .weapon
+                .charges(player, stats)
+                .map_or(true, |charges| self.in_flight < charges)
+    }
+
+    fn thrown(&mut self) {
+        self.cooldown.reset();
+        self.in_flight += 1;
+    }
+}
+
+/// The weapons the player carries, in the order of the weapon bar
+#[derive(Component)]
+pub struct Arsenal {
+    pub weapons: Vec<WeaponState>,
+}
+
+impl Arsenal {
+    /// The starting weapon first, then every weapon that has a key of its own
+    pub fn new(starting_weapon: Weapon) -> Self {
+        let others = Weapon::ALL.into_iter().filter(|weapon| {
+            *weapon != starting_weapon && weapon.key().is_some() && !weapon.is_evolution()
+        });
+        Arsenal {
+            weapons: std::iter::once(starting_weapon)
+                .chain(others)
+                .map(WeaponState::new)
+                .collect(),
+        }
+    }
+
+    pub fn has(&self, weapon: Weapon) -> bool {
+        self.weapons.iter().any(|state| state.weapon == weapon)
+    }
+
+    fn get_mut(&mut self, weapon: Weapon) -> Option<&mut WeaponState> {
+        self.weapons.iter_mut().find(|state| state.weapon == weapon)
+    }
+
+    /// Swaps the weapon for its evolution in the same slot, ready to be thrown
+    fn evolve(&mut self, weapon: Weapon, into: Weapon) {
+        if let Some(state) = self.get_mut(weapon) {
+            *state = WeaponState::new(into);
+        }
+    }
+}
+
 impl Plugin for ItemPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((BulletPlugin, HomingMissilePlugin, GranadePlugin));
+        app.add_plugins((
+            BulletPlugin,
+            BladeStormPlugin,
+            HomingMissilePlugin,
+            GranadePlugin,
+            ProjectilePlugin,
+            PassiveItemPlugin,
+        ))
+        .add_data_asset::<EvolutionList>()
+        .add_event::<WeaponEvolved>()
+        .add_systems(OnExit(GameState::Loading), evolution::check_evolutions)
+        .add_systems(Update, update_arsenal.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Counts down the cooldowns and counts the projectiles of every weapon that are still around
+fn update_arsenal(
+    time: Res<Time>,
+    mut arsenal_query: Query<(&mut Arsenal, &Stats)>,
+    projectile_query: Query<&Projectile>,
+) {
+    for (mut arsenal, stats) in &mut arsenal_query {
+        for state in &mut arsenal.weapons {
+            let cooldown = state.weapon.cooldown() * stats.get(Stat::Cooldown);
+            if state.cooldown.duration().as_secs_f32() != cooldown {
+                state
+                    .cooldown
+                    .set_duration(Duration::from_secs_f32(cooldown));
+            }
+            state.cooldown.tick(time.delta());
+            state.in_flight = projectile_query
+                .iter()
+                .filter(|projectile| projectile.weapon == state.weapon)
+                .count();
+        }
     }
 }
@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::Deserialize;
+
+use crate::player::{LevelUp, Player};
+use crate::run::Run;
+use crate::stats::{Modifier, ModifierSource, Stat, Stats};
+use crate::GameState;
+
+pub struct PassiveItemPlugin;
+
+/// Items that only improve a stat, every level up gives a level of one of them
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PassiveItem {
+    MoveSpeed,
+    CooldownReduction,
+    Area,
+    ProjectileCount,
+    Armor,
+    Regeneration,
+}
+
+impl PassiveItem {
+    pub const ALL: [PassiveItem; 6] = [
+        PassiveItem::MoveSpeed,
+        PassiveItem::CooldownReduction,
+        PassiveItem::Area,
+        PassiveItem::ProjectileCount,
+        PassiveItem::Armor,
+        PassiveItem::Regeneration,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PassiveItem::MoveSpeed => "Tabi boots",
+            PassiveItem::CooldownReduction => "Hourglass",
+            PassiveItem::Area => "Scroll of reach",
+            PassiveItem::ProjectileCount => "Extra pouch",
+            PassiveItem::Armor => "Chainmail",
+            PassiveItem::Regeneration => "Green tea",
+        }
+    }
+
+    /// What one level gives, in English so it can be translated
+    pub fn description(&self) -> &'static str {
+        match self {
+            PassiveItem::MoveSpeed => "+10% speed",
+            PassiveItem::CooldownReduction => "-8% cooldown",
+            PassiveItem::Area => "+10% area",
+            PassiveItem::ProjectileCount => "+1 projectile per throw",
+            PassiveItem::Armor => "-0.1 damage taken",
+            PassiveItem::Regeneration => "+0.05 health per second",
+        }
+    }
+
+    pub fn max_level(&self) -> u32 {
+        match self {
+            PassiveItem::ProjectileCount => 2,
+            _ => 5,
+        }
+    }
+
+    /// What the item does at this level
+    fn modifier(&self, level: u32) -> (Stat, Modifier) {
+        let level = level as f32;
+        match self {
+            PassiveItem::MoveSpeed => (Stat::MoveSpeed, Modifier::Multiply(1. + 0.1 * level)),
+            PassiveItem::CooldownReduction => {
+                (Stat::Cooldown, Modifier::Multiply(1. - 0.08 * level))
+            }
+            PassiveItem::Area => (Stat::Area, Modifier::Multiply(1. + 0.1 * level)),
+            PassiveItem::ProjectileCount => (Stat::Projectiles, Modifier::Add(level)),
+            PassiveItem::Armor => (Stat::Armor, Modifier::Add(0.1 * level)),
+            PassiveItem::Regeneration => (Stat::Regeneration, Modifier::Add(0.05 * level)),
+        }
+    }
+}
+
+/// The passive items the player picked up and their levels
+#[derive(Component, Default)]
+pub struct PassiveItems {
+    pub items: Vec<(PassiveItem, u32)>,
+}
+
+impl PassiveItems {
+    pub fn level(&self, item: PassiveItem) -> u32 {
+        self.items
+            .iter()
+            .find(|(owned, _)| *owned == item)
+            .map_or(0, |(_, level)| *level)
+    }
+
+    /// One more level of the item, returns the new level
+    fn add(&mut self, item: PassiveItem, stats: &mut Stats) -> u32 {
+        let level = match self.items.iter_mut().find(|(owned, _)| *owned == item) {
+            Some((_, level)) => {
+                *level += 1;
+                *level
+            }
+            None => {
+                self.items.push((item, 1));
+                1
+            }
+        };
+        stats.set(ModifierSource::Item(item), [item.modifier(level)]);
+        level
+    }
+}
+
+/// Sent when the player gets a level of a passive item
+#[derive(Event)]
+pub struct PassiveItemGained {
+    pub item: PassiveItem,
+    pub level: u32,
+    pub position: Vec2,
+}
+
+impl Plugin for PassiveItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PassiveItemGained>().add_systems(
+            Update,
+            gain_passive_items.run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Every level up gives a level of a random passive item that isn't maxed out yet
+fn gain_passive_items(
+    mut run: ResMut<Run>,
+    mut level_up_events: EventReader<LevelUp>,
+    mut player_query: Query<(&Transform, &mut PassiveItems, &mut Stats), With<Player>>,
+    mut gained_events: EventWriter<PassiveItemGained>,
+) {
+    let Ok((transform, mut items, mut stats)) = player_query.get_single_mut() else {
+        level_up_events.clear();
+        return;
+    };
+    for _ in level_up_events.read() {
+        let available: Vec<PassiveItem> = PassiveItem::ALL
+            .into_iter()
+            .filter(|item| items.level(*item) < item.max_level())
+            .collect();
+        let Some(item) = available.choose(&mut run.rng).copied() else {
+            continue;
+        };
+        let level = items.add(item, &mut stats);
+        gained_events.send(PassiveItemGained {
+            item,
+            level,
+            position: transform.translation.truncate(),
+        });
+    }
+}
@@ -0,0 +1,226 @@
+use bevy::math::bounding::{Aabb2d, IntersectsVolume};
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::enemy::{enemy_bounds, keep_in_bounds, Enemy, EnemyDamaged};
+use crate::player::Player;
+use crate::stats::{Stat, Stats};
+use crate::status::{StatusEffect, StatusEffects};
+use crate::GameState;
+
+use super::bullet::Bullet;
+use super::homing_missile::{HomingMissile, MissileBurst};
+use super::Weapon;
+
+// Ricochets only look for a new target this close to the hit
+const RICOCHET_RANGE: f32 = 300.;
+
+pub struct ProjectilePlugin;
+
+/// Something that happens to an enemy when a projectile hits it
+#[derive(Clone, Debug)]
+pub enum OnHitEffect {
+    /// Pushes the enemy away from the projectile by this many units
+    Knockback(f32),
+    Status(StatusEffect),
+}
+
+/// Shared behaviour of everything the player throws at the enemies
+#[derive(Component)]
+pub struct Projectile {
+    pub weapon: Weapon,
+    pub damage: f32,
+    /// How many more enemies this can pass through, u32::MAX never runs out
+    pub pierce: u32,
+    /// How many times this can ricochet to a new enemy once it can't pierce any more
+    pub bounces: u32,
+    /// Seconds before the same enemy can be hit again by this projectile
+    pub hit_cooldown: f32,
+    pub on_hit: Vec<OnHitEffect>,
+    recent_hits: Vec<(Entity, Timer)>,
+}
+
+impl Projectile {
+    pub fn new(weapon: Weapon, damage: f32) -> Self {
+        Projectile {
+            weapon,
+            damage,
+            pierce: 0,
+            bounces: 0,
+            hit_cooldown: 0.5,
+            on_hit: Vec::new(),
+            recent_hits: Vec::new(),
+        }
+    }
+
+    pub fn with_pierce(mut self, pierce: u32) -> Self {
+        self.pierce = pierce;
+        self
+    }
+
+    pub fn with_bounces(mut self, bounces: u32) -> Self {
+        self.bounces = bounces;
+        self
+    }
+
+    pub fn with_effect(mut self, effect: OnHitEffect) -> Self {
+        self.on_hit.push(effect);
+        self
+    }
+
+    fn recently_hit(&self, enemy: Entity) -> bool {
+        self.recent_hits.iter().any(|(hit, _)| *hit == enemy)
+    }
+}
+
+#[derive(Event)]
+pub struct ProjectileHit {
+    pub enemy: Entity,
+    /// Direction from the projectile to the enemy at the moment of the hit
+    pub direction: Vec2,
+    pub effects: Vec<OnHitEffect>,
+}
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ProjectileHit>().add_systems(
+            Update,
+            (tick_hit_cooldowns, projectile_hits, apply_on_hit_effects)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn tick_hit_cooldowns(time: Res<Time>, mut projectile_query: Query<&mut Projectile>) {
+    for mut projectile in &mut projectile_query {
+        projectile
+            .recent_hits
+            .retain_mut(|(_, timer)| !timer.tick(time.delta()).finished());
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn projectile_hits(
+    mut commands: Commands,
+    mut projectile_query: Query<(
+        Entity,
+        &Transform,
+        &mut Projectile,
+        Option<&mut Bullet>,
+        Option<&mut HomingMissile>,
+    )>,
+    mut enemy_query: Query<(Entity, &Transform, &mut Enemy, &StatusEffects), Without<Projectile>>,
+    player_query: Query<&Stats, (With<Player>, Without<Enemy>)>,
+    mut hit_events: EventWriter<ProjectileHit>,
+    mut damaged_events: EventWriter<EnemyDamaged>,
+    mut burst_events: EventWriter<MissileBurst>,
+) {
+    let might = player_query
+        .get_single()
+        .map_or(1., |stats| stats.get(Stat::Damage));
+    for (projectile_entity, projectile_transform, mut projectile, mut bullet, mut homing) in
+        &mut projectile_query
+    {
+        let position = projectile_transform.translation.truncate();
+        let projectile_box =
+            Aabb2d::new(position, projectile_transform.scale.truncate() * 10. / 2.);
+        let mut hits = Vec::new();
+        for (enemy_entity, enemy_transform, enemy, _) in &enemy_query {
+            if enemy.health <= 0. || projectile.recently_hit(enemy_entity) {
+                continue;
+            }
+            let collision = projectile_box.intersects(&Aabb2d::new(
+                enemy_transform.translation.truncate(),
+                enemy_transform.scale.truncate() * 5. / 2.,
+            ));
+            if collision {
+                hits.push((enemy_entity, enemy_transform.translation.truncate()));
+            }
+        }
+
+        for (enemy_entity, enemy_position) in hits {
+            let timer = Timer::from_seconds(projectile.hit_cooldown, TimerMode::Once);
+            projectile.recent_hits.push((enemy_entity, timer));
+            if let Ok((_, _, mut enemy, statuses)) = enemy_query.get_mut(enemy_entity) {
+                let amount = projectile.damage * might * statuses.damage_multiplier();
+                enemy.health -= amount;
+                enemy.last_hit_by = Some(projectile.weapon);
+                damaged_events.send(EnemyDamaged {
+                    entity: enemy_entity,
+                    position: enemy_position,
+                    amount,
+                });
+            }
+            hit_events.send(ProjectileHit {
+                enemy: enemy_entity,
+                direction: (enemy_position - position).normalize_or_zero(),
+                effects: projectile.on_hit.clone(),
+            });
+
+            if projectile.pierce > 0 {
+                if projectile.pierce != u32::MAX {
+                    projectile.pierce -= 1;
+                }
+            } else if projectile.bounces > 0 {
+                projectile.bounces -= 1;
+                // Ricochet towards the closest enemy this projectile hasn't just hit
+                let target = enemy_query
+                    .iter()
+                    .filter(|(entity, _, enemy, _)| {
+                        enemy.health > 0. && !projectile.recently_hit(*entity)
+                    })
+                    .map(|(_, transform, _, _)| transform.translation.truncate())
+                    .filter(|target| target.distance(position) < RICOCHET_RANGE)
+                    .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)));
+                if let Some(bullet) = bullet.as_mut() {
+                    bullet.direction = match target {
+                        Some(target) => (target - position).normalize_or_zero(),
+                        None => -bullet.direction,
+                    };
+                }
+                if let (Some(homing), Some(target)) = (homing.as_mut(), target) {
+                    homing.target = target.extend(homing.target.z);
+                }
+            } else {
+                if let Some(homing) = homing.as_ref().filter(|homing| homing.burst > 0) {
+                    burst_events.send(MissileBurst {
+                        position: enemy_position,
+                        count: homing.burst,
+                    });
+                }
+                commands.entity(projectile_entity).despawn_recursive();
+                break;
+            }
+        }
+    }
+}
+
+fn apply_on_hit_effects(
+    mut hit_events: EventReader<ProjectileHit>,
+    mut enemy_query: Query<(&mut Transform, &mut StatusEffects), With<Enemy>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let bounds = enemy_bounds(window);
+    for hit in hit_events.read() {
+        let Ok((mut enemy_transform, mut statuses)) = enemy_query.get_mut(hit.enemy) else {
+            continue;
+        };
+        for effect in &hit.effects {
+            match effect {
+                OnHitEffect::Knockback(distance) => {
+                    // Knocked back enemies can't leave the arena any more than walking ones
+                    let position = enemy_transform.translation.truncate();
+                    let new_pos =
+                        keep_in_bounds(bounds, position, position + hit.direction * *distance);
+                    enemy_transform.translation = new_pos.extend(enemy_transform.translation.z);
+                }
+                OnHitEffect::Status(status) => {
+                    statuses.apply(status.clone());
+                }
+            }
+        }
+    }
+}
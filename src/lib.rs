@@ -1,21 +1,62 @@
 #![allow(clippy::type_complexity)]
 
+mod achievements;
 mod actions;
+mod animation;
+mod audio;
+mod character;
+mod dash;
+mod data;
+mod disguise;
 mod enemy;
+mod feedback;
 mod item;
 mod level;
 mod loading;
 mod menu;
+mod minimap;
+#[cfg(feature = "online")]
+mod online;
+mod pickup;
 mod player;
+mod profile;
+mod run;
+mod settings;
+mod stats;
+mod status;
+mod storage;
 mod ui;
+mod upgrades;
+
+// The leaderboard server reads and writes the same JSON as the game
+#[cfg(feature = "online")]
+pub use crate::menu::leaderboard::LeaderboardEntry;
+#[cfg(feature = "online")]
+pub use crate::online::Submission;
+
+use crate::achievements::AchievementsPlugin;
 use crate::actions::ActionsPlugin;
+use crate::animation::AnimationPlugin;
+use crate::audio::InternalAudioPlugin;
+use crate::character::CharacterPlugin;
+use crate::dash::DashPlugin;
+use crate::disguise::DisguisePlugin;
 use crate::enemy::EnemyPlugin;
+use crate::feedback::FeedbackPlugin;
 use crate::item::ItemPlugin;
 use crate::level::LevelPlugin;
 use crate::loading::LoadingPlugin;
 use crate::menu::MenuPlugin;
+use crate::minimap::MinimapPlugin;
+use crate::pickup::PickupPlugin;
 use crate::player::PlayerPlugin;
+use crate::profile::ProfilePlugin;
+use crate::run::RunPlugin;
+use crate::settings::SettingsPlugin;
+use crate::stats::StatsPlugin;
+use crate::status::StatusPlugin;
 use crate::ui::UIPlugin;
+use crate::upgrades::UpgradesPlugin;
 
 use bevy::app::App;
 #[cfg(debug_assertions)]
@@ -43,16 +84,37 @@ pub struct GamePlugin;
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         // add_state is renamed init_state in 0.13
-        app.init_state::<GameState>().add_plugins((
-            LoadingPlugin,
-            MenuPlugin,
-            LevelPlugin,
-            ActionsPlugin,
-            PlayerPlugin,
-            UIPlugin,
-            ItemPlugin,
-            EnemyPlugin,
-        ));
+        app.init_state::<GameState>()
+            .add_plugins((
+                LoadingPlugin,
+                MenuPlugin,
+                LevelPlugin,
+                ActionsPlugin,
+                PlayerPlugin,
+                UIPlugin,
+                ItemPlugin,
+                EnemyPlugin,
+                StatusPlugin,
+                SettingsPlugin,
+                FeedbackPlugin,
+                InternalAudioPlugin,
+                RunPlugin,
+                ProfilePlugin,
+                AchievementsPlugin,
+            ))
+            .add_plugins((
+                PickupPlugin,
+                UpgradesPlugin,
+                CharacterPlugin,
+                AnimationPlugin,
+                DisguisePlugin,
+                DashPlugin,
+                MinimapPlugin,
+                StatsPlugin,
+            ));
+
+        #[cfg(feature = "online")]
+        app.add_plugins(online::OnlinePlugin);
 
         #[cfg(debug_assertions)]
         {
@@ -1,3 +1,7 @@
+use crate::achievements::AchievementList;
+use crate::character::CharacterList;
+use crate::item::EvolutionList;
+use crate::pickup::DropTables;
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
@@ -9,7 +13,9 @@ impl Plugin for LoadingPlugin {
         app.add_loading_state(
             LoadingState::new(GameState::Loading)
                 .continue_to_state(GameState::Menu)
-                .load_collection::<TextureAssets>(),
+                .load_collection::<TextureAssets>()
+                .load_collection::<AudioAssets>()
+                .load_collection::<DataAssets>(),
         );
     }
 }
@@ -27,6 +33,52 @@ pub struct TextureAssets {
     pub character: Handle<Image>,
     #[asset(path = "cactus.png")]
     pub cactus: Handle<Image>,
-    #[asset(path = "enemy.png")]
-    pub ninja: Handle<Image>,
+    /// The frames of `ninja_sheet.png` and `monk_sheet.png`
+    #[asset(texture_atlas(tile_size_x = 16., tile_size_y = 16., columns = 10, rows = 1))]
+    pub character_layout: Handle<TextureAtlasLayout>,
+    #[asset(image(sampler=nearest))]
+    #[asset(path = "ninja_sheet.png")]
+    pub ninja_sheet: Handle<Image>,
+    #[asset(image(sampler=nearest))]
+    #[asset(path = "monk_sheet.png")]
+    pub monk_sheet: Handle<Image>,
+    /// The icons of `pack/icons.png`, 16 to a row
+    #[asset(texture_atlas(tile_size_x = 32., tile_size_y = 32., columns = 16, rows = 27))]
+    pub icons_layout: Handle<TextureAtlasLayout>,
+    #[asset(image(sampler=nearest))]
+    #[asset(path = "pack/icons.png")]
+    pub icons: Handle<Image>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct AudioAssets {
+    #[asset(path = "audio/throw.wav")]
+    pub throw: Handle<AudioSource>,
+    #[asset(path = "audio/hit.wav")]
+    pub hit: Handle<AudioSource>,
+    #[asset(path = "audio/kill.wav")]
+    pub kill: Handle<AudioSource>,
+    #[asset(path = "audio/level_up.wav")]
+    pub level_up: Handle<AudioSource>,
+    #[asset(path = "audio/explosion.wav")]
+    pub explosion: Handle<AudioSource>,
+    #[asset(path = "audio/death.wav")]
+    pub death: Handle<AudioSource>,
+    #[asset(path = "audio/menu_music.wav")]
+    pub menu_music: Handle<AudioSource>,
+    #[asset(path = "audio/gameplay_music.wav")]
+    pub gameplay_music: Handle<AudioSource>,
+}
+
+/// Content declared in `assets/data`, see [`crate::data`]
+#[derive(AssetCollection, Resource)]
+pub struct DataAssets {
+    #[asset(path = "data/game.achievements.toml")]
+    pub achievements: Handle<AchievementList>,
+    #[asset(path = "data/game.characters.toml")]
+    pub characters: Handle<CharacterList>,
+    #[asset(path = "data/game.drops.toml")]
+    pub drops: Handle<DropTables>,
+    #[asset(path = "data/game.evolutions.toml")]
+    pub evolutions: Handle<EvolutionList>,
 }
@@ -0,0 +1,159 @@
+use bevy::prelude::*;
+
+use crate::achievements::{AchievementList, UnlockedAchievements};
+use crate::loading::DataAssets;
+use crate::settings::Settings;
+
+use super::leaderboard::format_date;
+use super::{
+    cleanup_screen, screen_root, spawn_button, MenuButtonAction, MenuScreen, TranslatedText,
+    TEXT_COLOR,
+};
+
+const UNLOCKED_COLOR: Color = Color::GOLD;
+const LOCKED_COLOR: Color = Color::rgb(0.5, 0.5, 0.5);
+
+pub struct AchievementsMenuPlugin;
+
+#[derive(Component)]
+struct AchievementsMenu;
+
+impl Plugin for AchievementsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuScreen::Achievements), setup_achievements_menu)
+            .add_systems(
+                OnExit(MenuScreen::Achievements),
+                cleanup_screen::<AchievementsMenu>,
+            );
+    }
+}
+
+fn setup_achievements_menu(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    data: Res<DataAssets>,
+    lists: Res<Assets<AchievementList>>,
+    unlocked: Res<UnlockedAchievements>,
+) {
+    let language = settings.language;
+    let achievements = lists
+        .get(&data.achievements)
+        .map_or(&[][..], |list| &list.achievements);
+    let unlocked_count = achievements
+        .iter()
+        .filter(|achievement| unlocked.get(&achievement.id).is_some())
+        .count();
+    commands
+        .spawn((screen_root(), AchievementsMenu))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    language.tr("Achievements"),
+                    TextStyle {
+                        font_size: 40.0,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                ),
+                TranslatedText("Achievements"),
+            ));
+            parent.spawn(
+                TextBundle::from_section(
+                    format!("{unlocked_count} / {}", achievements.len()),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: UNLOCKED_COLOR,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                }),
+            );
+            // Two columns so the list fits on small screens
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        max_width: Val::Px(760.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for achievement in achievements {
+                        let unlock = unlocked.get(&achievement.id);
+                        let color = if unlock.is_some() {
+                            UNLOCKED_COLOR
+                        } else {
+                            LOCKED_COLOR
+                        };
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    width: Val::Px(360.0),
+                                    margin: UiRect::all(Val::Px(5.0)),
+                                    padding: UiRect::all(Val::Px(6.0)),
+                                    border: UiRect::all(Val::Px(2.0)),
+                                    flex_direction: FlexDirection::Column,
+                                    ..default()
+                                },
+                                border_color: color.into(),
+                                background_color: Color::rgb(0.15, 0.15, 0.15).into(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                let mut title = vec![TextSection::new(
+                                    achievement.name.clone(),
+                                    TextStyle {
+                                        font_size: 20.0,
+                                        color,
+                                        ..default()
+                                    },
+                                )];
+                                if let Some(unlock) = unlock {
+                                    title.push(TextSection::new(
+                                        format!("  {}", format_date(unlock.date)),
+                                        TextStyle {
+                                            font_size: 14.0,
+                                            color: LOCKED_COLOR,
+                                            ..default()
+                                        },
+                                    ));
+                                }
+                                parent.spawn(TextBundle::from_sections(title));
+                                parent.spawn(TextBundle::from_section(
+                                    achievement.description.clone(),
+                                    TextStyle {
+                                        font_size: 16.0,
+                                        color: TEXT_COLOR,
+                                        ..default()
+                                    },
+                                ));
+                            });
+                    }
+                });
+            spawn_button(
+                parent,
+                "Back",
+                MenuButtonAction::Back,
+                Style {
+                    width: Val::Px(250.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                TextStyle {
+                    font_size: 30.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+                language,
+            );
+        });
+}
@@ -0,0 +1,228 @@
+use bevy::prelude::*;
+
+use crate::character::{selected_character, Character, CharacterList};
+use crate::loading::{DataAssets, TextureAssets};
+use crate::settings::{Language, Settings};
+use crate::GameState;
+
+use super::navigation::{Focusable, MenuActivated};
+use super::{
+    cleanup_screen, screen_root, spawn_button, ButtonColors, MenuButtonAction, MenuScreen,
+    TranslatedText, TEXT_COLOR,
+};
+
+const SELECTED_COLOR: Color = Color::GOLD;
+const DETAIL_COLOR: Color = Color::rgb(0.7, 0.7, 0.7);
+
+pub struct CharactersMenuPlugin;
+
+#[derive(Component)]
+struct CharactersMenu;
+
+/// Starts a run with the character at this index of [`CharacterList::characters`]
+#[derive(Component)]
+struct CharacterButton(usize);
+
+impl Plugin for CharactersMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuScreen::Characters), setup_characters_menu)
+            .add_systems(
+                Update,
+                pick_character.run_if(in_state(MenuScreen::Characters)),
+            )
+            .add_systems(
+                OnExit(MenuScreen::Characters),
+                cleanup_screen::<CharactersMenu>,
+            );
+    }
+}
+
+/// The lines under a character's name, with what it starts with
+fn detail_lines(character: &Character, language: Language) -> Vec<String> {
+    let stats = character.stats;
+    let mut lines = vec![
+        format!(
+            "{}: {}",
+            language.tr("Weapon"),
+            language.tr(character.starting_weapon.name())
+        ),
+        format!("{}: {}", language.tr("Health"), stats.max_health),
+        format!(
+            "{}: {:.0}%",
+            language.tr("Move speed"),
+            stats.move_speed * 100.
+        ),
+    ];
+    if let Some(passive) = character.passive {
+        lines.push(format!(
+            "{}: {} ({})",
+            language.tr("Passive"),
+            language.tr(passive.name()),
+            passive.amount()
+        ));
+    }
+    lines
+}
+
+fn setup_characters_menu(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    textures: Res<TextureAssets>,
+    data: Res<DataAssets>,
+    lists: Res<Assets<CharacterList>>,
+) {
+    let language = settings.language;
+    let characters = lists
+        .get(&data.characters)
+        .map_or(&[][..], |list| &list.characters);
+    let selected = selected_character(&settings, &data, &lists).map(|character| &character.id);
+    commands
+        .spawn((screen_root(), CharactersMenu))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    language.tr("Choose a character"),
+                    TextStyle {
+                        font_size: 40.0,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                }),
+                TranslatedText("Choose a character"),
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        max_width: Val::Px(900.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (index, character) in characters.iter().enumerate() {
+                        let button_colors = ButtonColors::default();
+                        let border_color = if selected == Some(&character.id) {
+                            SELECTED_COLOR
+                        } else {
+                            Color::NONE
+                        };
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(260.0),
+                                        margin: UiRect::all(Val::Px(8.0)),
+                                        padding: UiRect::all(Val::Px(8.0)),
+                                        border: UiRect::all(Val::Px(2.0)),
+                                        flex_direction: FlexDirection::Column,
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    border_color: border_color.into(),
+                                    background_color: button_colors.normal.into(),
+                                    ..default()
+                                },
+                                button_colors,
+                                CharacterButton(index),
+                                Focusable,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(AtlasImageBundle {
+                                    style: Style {
+                                        width: Val::Px(64.0),
+                                        height: Val::Px(64.0),
+                                        ..default()
+                                    },
+                                    image: character.sprite_sheet.image(&textures).into(),
+                                    texture_atlas: TextureAtlas {
+                                        layout: textures.character_layout.clone(),
+                                        index: 1,
+                                    },
+                                    ..default()
+                                });
+                                parent.spawn(TextBundle::from_section(
+                                    character.name.clone(),
+                                    TextStyle {
+                                        font_size: 26.0,
+                                        color: TEXT_COLOR,
+                                        ..default()
+                                    },
+                                ));
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        character.description.clone(),
+                                        TextStyle {
+                                            font_size: 16.0,
+                                            color: TEXT_COLOR,
+                                            ..default()
+                                        },
+                                    )
+                                    .with_text_justify(JustifyText::Center)
+                                    .with_style(Style {
+                                        margin: UiRect::vertical(Val::Px(5.0)),
+                                        ..default()
+                                    }),
+                                );
+                                for line in detail_lines(character, language) {
+                                    parent.spawn(TextBundle::from_section(
+                                        line,
+                                        TextStyle {
+                                            font_size: 16.0,
+                                            color: DETAIL_COLOR,
+                                            ..default()
+                                        },
+                                    ));
+                                }
+                            });
+                    }
+                });
+            spawn_button(
+                parent,
+                "Back",
+                MenuButtonAction::Back,
+                Style {
+                    width: Val::Px(250.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                TextStyle {
+                    font_size: 30.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+                language,
+            );
+        });
+}
+
+fn pick_character(
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<GameState>>,
+    data: Res<DataAssets>,
+    lists: Res<Assets<CharacterList>>,
+    button_query: Query<&CharacterButton>,
+    mut activated_events: EventReader<MenuActivated>,
+) {
+    let Some(list) = lists.get(&data.characters) else {
+        return;
+    };
+    for MenuActivated(entity) in activated_events.read() {
+        let Ok(CharacterButton(index)) = button_query.get(*entity) else {
+            continue;
+        };
+        if let Some(character) = list.characters.get(*index) {
+            settings.character = character.id.clone();
+            next_state.set(GameState::Playing);
+        }
+    }
+}
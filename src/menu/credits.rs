@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+
+use crate::loading::TextureAssets;
+use crate::settings::Settings;
+
+use super::navigation::Focusable;
+use super::{
+    cleanup_screen, screen_root, spawn_button, ButtonColors, MenuButtonAction, MenuScreen,
+    OpenLink, TranslatedText, TEXT_COLOR,
+};
+
+const CREDITS: [&str; 3] = [
+    "Ninja Killers",
+    "Bevy icon: MIT License",
+    "Sound effects and music synthesized for this game",
+];
+
+const LINKS: [(&str, &str); 2] = [
+    ("Made with Bevy", "https://bevyengine.org"),
+    ("Source", "https://github.com/idjotherwise"),
+];
+
+pub struct CreditsMenuPlugin;
+
+#[derive(Component)]
+struct CreditsMenu;
+
+impl Plugin for CreditsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuScreen::Credits), setup_credits_menu)
+            .add_systems(OnExit(MenuScreen::Credits), cleanup_screen::<CreditsMenu>);
+    }
+}
+
+fn setup_credits_menu(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    settings: Res<Settings>,
+) {
+    let text_style = TextStyle {
+        font_size: 22.0,
+        color: Color::rgb(0.9, 0.9, 0.9),
+        ..default()
+    };
+    commands
+        .spawn((screen_root(), CreditsMenu))
+        .with_children(|parent| {
+            for line in CREDITS {
+                parent.spawn(
+                    TextBundle::from_section(line, text_style.clone()).with_style(Style {
+                        margin: UiRect::all(Val::Px(5.0)),
+                        ..default()
+                    }),
+                );
+            }
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        margin: UiRect::top(Val::Px(20.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (label, link) in LINKS {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(200.0),
+                                        height: Val::Px(50.0),
+                                        margin: UiRect::all(Val::Px(10.0)),
+                                        align_items: AlignItems::Center,
+                                        justify_content: JustifyContent::SpaceAround,
+                                        padding: UiRect::all(Val::Px(5.)),
+                                        ..default()
+                                    },
+                                    background_color: Color::NONE.into(),
+                                    ..default()
+                                },
+                                ButtonColors {
+                                    normal: Color::NONE,
+                                    ..default()
+                                },
+                                OpenLink(link),
+                                MenuButtonAction::OpenLink,
+                                Focusable,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    TextBundle::from_section(
+                                        settings.language.tr(label),
+                                        TextStyle {
+                                            font_size: 15.0,
+                                            ..text_style.clone()
+                                        },
+                                    ),
+                                    TranslatedText(label),
+                                ));
+                                parent.spawn(ImageBundle {
+                                    image: textures.bevy.clone().into(),
+                                    style: Style {
+                                        width: Val::Px(32.),
+                                        ..default()
+                                    },
+                                    ..default()
+                                });
+                            });
+                    }
+                });
+            spawn_button(
+                parent,
+                "Back",
+                MenuButtonAction::Back,
+                Style {
+                    width: Val::Px(250.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::top(Val::Px(20.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                TextStyle {
+                    font_size: 30.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+                settings.language,
+            );
+        });
+}
@@ -1,44 +1,788 @@
+use std::cmp::Ordering;
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "online")]
+use crate::online::{FetchTopRuns, OnlineLeaderboard, ServerStatus};
+use crate::run::{Arena, Difficulty};
+use crate::settings::Settings;
+use crate::storage;
+
+use super::navigation::{Focusable, MenuActivated};
+use super::{
+    cleanup_screen, screen_root, spawn_button, ButtonColors, MenuButtonAction, MenuScreen,
+    TranslatedText, TEXT_COLOR,
+};
+
+const LEADERBOARD_FILE: &str = "leaderboard.toml";
+const DEFAULT_CAPACITY: usize = 10;
+const ENTRY_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const LATEST_ENTRY_COLOR: Color = Color::GOLD;
+const SORTED_HEADER_COLOR: Color = Color::GOLD;
 
-#[derive(Resource, Clone, Default, Debug)]
+pub struct LeaderboardMenuPlugin;
+
+/// One finished run
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub score: i32,
+    pub level: i32,
+    /// In seconds
+    pub survival_time: f32,
+    pub kills: u32,
+    /// Seconds since the Unix epoch
+    pub date: u64,
+    pub seed: u32,
+    pub version: String,
+    pub difficulty: Difficulty,
+    pub arena: Arena,
+    /// The id of the character, empty for runs from before there were characters
+    #[serde(default)]
+    pub character: String,
+}
+
+/// Orders entries from best to worst, a higher score wins and the earlier run breaks ties
+fn rank_order(a: &LeaderboardEntry, b: &LeaderboardEntry) -> Ordering {
+    b.score.cmp(&a.score).then(a.date.cmp(&b.date))
+}
+
+fn default_capacity() -> usize {
+    DEFAULT_CAPACITY
+}
+
+/// The best runs of every difficulty and arena, saved whenever a run makes it on
+#[derive(Resource, Serialize, Deserialize, Debug)]
 pub struct Leaderboard {
-    pub leaderboard: Vec<(PlayerName, Score)>,
+    /// How many runs are kept for every difficulty and arena
+    #[serde(default = "default_capacity")]
+    capacity: usize,
+    /// Sorted by [`rank_order`]
+    #[serde(default)]
+    entries: Vec<LeaderboardEntry>,
+    /// The entry of the run that just ended, if it made it on
+    #[serde(skip)]
+    latest: Option<LeaderboardEntry>,
+}
+
+impl Default for Leaderboard {
+    fn default() -> Self {
+        Leaderboard::new(DEFAULT_CAPACITY)
+    }
 }
 
 impl Leaderboard {
-    pub fn default() -> Self {
+    pub fn new(capacity: usize) -> Self {
         Leaderboard {
-            leaderboard: vec![(PlayerName("".to_string()), Score { score: 0 }); 10],
+            capacity,
+            entries: Vec::new(),
+            latest: None,
+        }
+    }
+
+    pub fn load() -> Self {
+        let Some(contents) = storage::load(LEADERBOARD_FILE) else {
+            return Leaderboard::default();
+        };
+        let mut leaderboard: Leaderboard = toml::from_str(&contents).unwrap_or_else(|error| {
+            warn!("Ignoring invalid {LEADERBOARD_FILE}: {error}");
+            Leaderboard::default()
+        });
+        // The file may have been edited by hand
+        leaderboard.entries.sort_by(rank_order);
+        leaderboard.trim_tables();
+        leaderboard
+    }
+
+    fn save(&self) {
+        match toml::to_string(self) {
+            Ok(contents) => storage::save(LEADERBOARD_FILE, &contents),
+            Err(error) => warn!("Failed to save the leaderboard: {error}"),
         }
     }
 
-    pub fn add_score(&mut self, name: String, score: i32) {
-        self.leaderboard.sort_by(|a, b| a.1.cmp(&b.1));
-        let pos = self
-            .leaderboard
-            .binary_search_by(|(_, b)| b.cmp(&Score { score }))
-            .unwrap_or_else(|e| e);
-        self.leaderboard
-            .insert(pos, (PlayerName(name), Score { score }));
-        self.leaderboard.reverse();
-        self.leaderboard.pop();
+    /// Drops the worst runs of every table that holds more than the capacity
+    fn trim_tables(&mut self) {
+        let capacity = self.capacity;
+        let mut counts: Vec<((Difficulty, Arena), usize)> = Vec::new();
+        self.entries.retain(|entry| {
+            let table = (entry.difficulty, entry.arena);
+            let count = match counts.iter_mut().find(|(other, _)| *other == table) {
+                Some((_, count)) => count,
+                None => {
+                    counts.push((table, 0));
+                    &mut counts.last_mut().unwrap().1
+                }
+            };
+            *count += 1;
+            *count <= capacity
+        });
+    }
+
+    /// Records a finished run, returning its rank starting at 1 if it made it on
+    pub fn add_entry(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        self.latest = None;
+        let rank = self.insert(entry.clone())?;
+        self.latest = Some(entry);
+        self.save();
+        Some(rank)
+    }
+
+    /// Puts the entry in its place and trims its table, returning its rank if it is still on
+    fn insert(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        // After every entry that ranks the same, so the earlier run stays ahead
+        let index = self
+            .entries
+            .partition_point(|other| rank_order(other, &entry) != Ordering::Greater);
+        let rank = self.entries[..index]
+            .iter()
+            .filter(|other| other.difficulty == entry.difficulty && other.arena == entry.arena)
+            .count()
+            + 1;
+        self.entries.insert(index, entry);
+        self.trim_tables();
+        (rank <= self.capacity).then_some(rank)
+    }
+
+    /// Where an entry is in its table, starting at 1
+    pub fn rank(&self, entry: &LeaderboardEntry) -> Option<usize> {
+        self.table(entry.difficulty, entry.arena)
+            .position(|other| other == entry)
+            .map(|index| index + 1)
+    }
+
+    /// The rank of the run that just ended, if it made it on
+    pub fn latest_rank(&self) -> Option<usize> {
+        self.rank(self.latest.as_ref()?)
+    }
+
+    /// The runs of one difficulty and arena, best first
+    pub fn table(
+        &self,
+        difficulty: Difficulty,
+        arena: Arena,
+    ) -> impl Iterator<Item = &LeaderboardEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.difficulty == difficulty && entry.arena == arena)
     }
 }
 
-#[derive(Resource, Default, Debug, Clone)]
-pub struct PlayerName(pub String);
+/// Seconds since the Unix epoch
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Seconds since the Unix epoch
+#[cfg(target_arch = "wasm32")]
+pub fn unix_time() -> u64 {
+    (js_sys::Date::now() / 1000.) as u64
+}
+
+/// Formats a Unix timestamp as a year-month-day date in UTC
+pub(super) fn format_date(timestamp: u64) -> String {
+    // Howard Hinnant's days to civil date algorithm
+    let days = (timestamp / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year}-{month:02}-{day:02}")
+}
+
+pub(super) fn format_time(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
 
 // Unit struct to identify the player name text bundle
 #[derive(Component)]
 pub struct NameText;
 
-impl PlayerName {
-    pub fn set(&mut self, new_name: &String) {
-        self.0 = new_name.to_string();
-    }
-}
-
 #[derive(Resource, Debug, Copy, Default, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Score {
     pub score: i32,
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+enum Column {
+    Rank,
+    Name,
+    #[default]
+    Score,
+    Level,
+    Time,
+    Kills,
+    Character,
+    Date,
+    Seed,
+    Version,
+}
+
+const COLUMNS: [Column; 10] = [
+    Column::Rank,
+    Column::Name,
+    Column::Score,
+    Column::Level,
+    Column::Time,
+    Column::Kills,
+    Column::Character,
+    Column::Date,
+    Column::Seed,
+    Column::Version,
+];
+
+impl Column {
+    fn label(&self) -> &'static str {
+        match self {
+            Column::Rank => "#",
+            Column::Name => "Name",
+            Column::Score => "Score",
+            Column::Level => "Level",
+            Column::Time => "Time",
+            Column::Kills => "Kills",
+            Column::Character => "Character",
+            Column::Date => "Date",
+            Column::Seed => "Seed",
+            Column::Version => "Version",
+        }
+    }
+
+    fn width(&self) -> f32 {
+        match self {
+            Column::Rank => 40.,
+            Column::Name => 170.,
+            Column::Character => 110.,
+            Column::Date | Column::Seed => 130.,
+            _ => 85.,
+        }
+    }
+
+    fn cell(&self, rank: usize, entry: &LeaderboardEntry) -> String {
+        match self {
+            Column::Rank => rank.to_string(),
+            Column::Name => entry.name.clone(),
+            Column::Score => entry.score.to_string(),
+            Column::Level => entry.level.to_string(),
+            Column::Time => format_time(entry.survival_time),
+            Column::Kills => entry.kills.to_string(),
+            Column::Character => entry.character.clone(),
+            Column::Date => format_date(entry.date),
+            Column::Seed => entry.seed.to_string(),
+            Column::Version => entry.version.clone(),
+        }
+    }
+
+    /// Orders the entries with the most impressive one first, names alphabetically
+    fn compare(&self, a: &LeaderboardEntry, b: &LeaderboardEntry) -> Ordering {
+        match self {
+            Column::Rank | Column::Score => rank_order(a, b),
+            Column::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            Column::Level => b.level.cmp(&a.level),
+            Column::Time => b.survival_time.total_cmp(&a.survival_time),
+            Column::Kills => b.kills.cmp(&a.kills),
+            Column::Character => a.character.cmp(&b.character),
+            Column::Date => b.date.cmp(&a.date),
+            Column::Seed => a.seed.cmp(&b.seed),
+            Column::Version => b.version.cmp(&a.version),
+        }
+    }
+}
+
+/// Which table is shown and how it is sorted
+#[derive(Resource, Default)]
+struct LeaderboardView {
+    sort: Column,
+    difficulty: Difficulty,
+    arena: Arena,
+    /// Showing everyone's runs from the leaderboard server instead of the ones on this device
+    #[cfg(feature = "online")]
+    global: bool,
+}
+
+#[derive(Component)]
+enum LeaderboardButton {
+    Sort(Column),
+    Difficulty,
+    Arena,
+    #[cfg(feature = "online")]
+    Source,
+}
+
+#[derive(Component)]
+struct LeaderboardMenu;
+
+#[derive(Component)]
+struct LeaderboardRows;
+
+impl Plugin for LeaderboardMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LeaderboardView>()
+            .add_systems(OnEnter(MenuScreen::Leaderboard), setup_leaderboard_menu)
+            .add_systems(
+                Update,
+                (leaderboard_action, update_leaderboard_table)
+                    .chain()
+                    .run_if(in_state(MenuScreen::Leaderboard)),
+            )
+            .add_systems(
+                OnExit(MenuScreen::Leaderboard),
+                cleanup_screen::<LeaderboardMenu>,
+            );
+    }
+}
+
+fn setup_leaderboard_menu(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    leaderboard: Res<Leaderboard>,
+    mut view: ResMut<LeaderboardView>,
+) {
+    // Start on the table of the run that just ended
+    *view = match &leaderboard.latest {
+        Some(latest) => LeaderboardView {
+            difficulty: latest.difficulty,
+            arena: latest.arena,
+            ..default()
+        },
+        None => LeaderboardView {
+            difficulty: settings.difficulty,
+            ..default()
+        },
+    };
+
+    let button_text_style = TextStyle {
+        font_size: 22.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    commands
+        .spawn((screen_root(), LeaderboardMenu))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    settings.language.tr("Leaderboard"),
+                    TextStyle {
+                        font_size: 40.0,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                }),
+                TranslatedText("Leaderboard"),
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        margin: UiRect::bottom(Val::Px(10.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for button in [
+                        LeaderboardButton::Difficulty,
+                        LeaderboardButton::Arena,
+                        #[cfg(feature = "online")]
+                        LeaderboardButton::Source,
+                    ] {
+                        let button_colors = ButtonColors::default();
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(300.0),
+                                        height: Val::Px(36.0),
+                                        margin: UiRect::horizontal(Val::Px(10.0)),
+                                        align_items: AlignItems::Center,
+                                        justify_content: JustifyContent::Center,
+                                        ..default()
+                                    },
+                                    background_color: button_colors.normal.into(),
+                                    ..default()
+                                },
+                                button_colors,
+                                button,
+                                Focusable,
+                            ))
+                            .with_children(|parent| {
+                                parent
+                                    .spawn(TextBundle::from_section("", button_text_style.clone()));
+                            });
+                    }
+                });
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for column in COLUMNS {
+                        let button_colors = ButtonColors::default();
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(column.width()),
+                                        height: Val::Px(32.0),
+                                        align_items: AlignItems::Center,
+                                        justify_content: JustifyContent::Center,
+                                        ..default()
+                                    },
+                                    background_color: button_colors.normal.into(),
+                                    ..default()
+                                },
+                                button_colors,
+                                LeaderboardButton::Sort(column),
+                                Focusable,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    "",
+                                    TextStyle {
+                                        font_size: 18.0,
+                                        ..button_text_style.clone()
+                                    },
+                                ));
+                            });
+                    }
+                });
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    ..default()
+                },
+                LeaderboardRows,
+            ));
+            spawn_button(
+                parent,
+                "Back",
+                MenuButtonAction::Back,
+                Style {
+                    width: Val::Px(250.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::top(Val::Px(20.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                TextStyle {
+                    font_size: 30.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+                settings.language,
+            );
+        });
+}
+
+fn leaderboard_action(
+    mut view: ResMut<LeaderboardView>,
+    button_query: Query<&LeaderboardButton>,
+    mut activated_events: EventReader<MenuActivated>,
+    #[cfg(feature = "online")] mut fetch_events: EventWriter<FetchTopRuns>,
+) {
+    for MenuActivated(entity) in activated_events.read() {
+        match button_query.get(*entity) {
+            Ok(LeaderboardButton::Sort(column)) => {
+                view.sort = *column;
+                continue;
+            }
+            Ok(LeaderboardButton::Difficulty) => view.difficulty = view.difficulty.next(),
+            Ok(LeaderboardButton::Arena) => view.arena = view.arena.next(),
+            #[cfg(feature = "online")]
+            Ok(LeaderboardButton::Source) => view.global = !view.global,
+            Err(_) => continue,
+        }
+        // A different table is shown, so it has to come from the server again
+        #[cfg(feature = "online")]
+        if view.global {
+            fetch_events.send(FetchTopRuns {
+                difficulty: view.difficulty,
+                arena: view.arena,
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_leaderboard_table(
+    mut commands: Commands,
+    view: Res<LeaderboardView>,
+    settings: Res<Settings>,
+    leaderboard: Res<Leaderboard>,
+    rows_query: Query<Entity, With<LeaderboardRows>>,
+    button_query: Query<(&LeaderboardButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+    #[cfg(feature = "online")] online: Res<OnlineLeaderboard>,
+) {
+    #[allow(unused_mut)]
+    let mut changed = view.is_changed() || settings.is_changed() || leaderboard.is_changed();
+    #[cfg(feature = "online")]
+    {
+        changed |= online.is_changed();
+    }
+    if !changed {
+        return;
+    }
+    let language = settings.language;
+
+    for (button, children) in &button_query {
+        let Some(mut text) = children
+            .first()
+            .and_then(|child| text_query.get_mut(*child).ok())
+        else {
+            continue;
+        };
+        let section = &mut text.sections[0];
+        match button {
+            LeaderboardButton::Sort(column) => {
+                section.value = language.tr(column.label()).to_string();
+                section.style.color = if *column == view.sort {
+                    SORTED_HEADER_COLOR
+                } else {
+                    TEXT_COLOR
+                };
+            }
+            LeaderboardButton::Difficulty => {
+                section.value = format!(
+                    "{}: {}",
+                    language.tr("Difficulty"),
+                    language.tr(view.difficulty.name())
+                );
+            }
+            LeaderboardButton::Arena => {
+                section.value = format!(
+                    "{}: {}",
+                    language.tr("Arena"),
+                    language.tr(view.arena.name())
+                );
+            }
+            #[cfg(feature = "online")]
+            LeaderboardButton::Source => {
+                section.value = format!(
+                    "{}: {}",
+                    language.tr("Runs"),
+                    language.tr(if view.global {
+                        "Everyone"
+                    } else {
+                        "This device"
+                    })
+                );
+            }
+        }
+    }
+
+    let Ok(rows) = rows_query.get_single() else {
+        return;
+    };
+    let table: Vec<&LeaderboardEntry> = leaderboard.table(view.difficulty, view.arena).collect();
+    #[cfg(feature = "online")]
+    let table: Vec<&LeaderboardEntry> = if view.global {
+        online.top.iter().collect()
+    } else {
+        table
+    };
+    let mut messages = Vec::new();
+    if table.is_empty() {
+        messages.push(language.tr("No runs yet").to_string());
+    }
+    #[cfg(feature = "online")]
+    if view.global {
+        match online.status {
+            ServerStatus::Loading => messages = vec![language.tr("Loading...").to_string()],
+            ServerStatus::Unreachable => {
+                messages = vec![language
+                    .tr("The leaderboard server can't be reached")
+                    .to_string()]
+            }
+            ServerStatus::Idle | ServerStatus::Online => {}
+        }
+        if online.queued > 0 {
+            messages.push(format!(
+                "{} {}",
+                online.queued,
+                language.tr("runs are waiting to be sent")
+            ));
+        }
+    }
+    let mut entries: Vec<_> = table
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| (index + 1, entry))
+        .collect();
+    // Stable, so entries that compare equal stay in rank order
+    entries.sort_by(|(_, a), (_, b)| view.sort.compare(a, b));
+
+    commands.entity(rows).despawn_descendants();
+    commands.entity(rows).with_children(|parent| {
+        for message in messages {
+            parent.spawn(
+                TextBundle::from_section(
+                    message,
+                    TextStyle {
+                        font_size: 18.0,
+                        color: ENTRY_COLOR,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(10.0)),
+                    align_self: AlignSelf::Center,
+                    ..default()
+                }),
+            );
+        }
+        for (rank, entry) in entries {
+            let latest = leaderboard.latest.as_ref() == Some(entry);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        height: Val::Px(26.0),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: if latest {
+                        Color::rgba(1., 0.84, 0., 0.2).into()
+                    } else {
+                        Color::NONE.into()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for column in COLUMNS {
+                        parent.spawn(
+                            TextBundle::from_section(
+                                column.cell(rank, entry),
+                                TextStyle {
+                                    font_size: 18.0,
+                                    color: if latest {
+                                        LATEST_ENTRY_COLOR
+                                    } else {
+                                        ENTRY_COLOR
+                                    },
+                                    ..default()
+                                },
+                            )
+                            .with_text_justify(JustifyText::Center)
+                            .with_style(Style {
+                                width: Val::Px(column.width()),
+                                ..default()
+                            }),
+                        );
+                    }
+                });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn entry(id: usize, score: i32, date: u64, difficulty: Difficulty) -> LeaderboardEntry {
+        LeaderboardEntry {
+            name: format!("Run {id}"),
+            score,
+            level: 1,
+            survival_time: 0.,
+            kills: 0,
+            date,
+            seed: id as u32,
+            version: String::new(),
+            difficulty,
+            arena: Arena::Desert,
+            character: String::new(),
+        }
+    }
+
+    /// Runs with only a few different scores and dates, so there are plenty of ties
+    fn runs() -> impl Strategy<Value = Vec<LeaderboardEntry>> {
+        prop::collection::vec(
+            (
+                -3..12i32,
+                0..8u64,
+                prop::sample::select(Difficulty::ALL.to_vec()),
+            ),
+            0..60,
+        )
+        .prop_map(|runs| {
+            runs.into_iter()
+                .enumerate()
+                .map(|(id, (score, date, difficulty))| entry(id, score, date, difficulty))
+                .collect()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn tables_stay_ranked_and_within_capacity(capacity in 1..8usize, runs in runs()) {
+            let mut leaderboard = Leaderboard::new(capacity);
+            for run in runs {
+                let rank = leaderboard.insert(run.clone());
+                prop_assert_eq!(rank, leaderboard.rank(&run));
+                for difficulty in Difficulty::ALL {
+                    for arena in Arena::ALL {
+                        let table: Vec<_> = leaderboard.table(difficulty, arena).collect();
+                        prop_assert!(table.len() <= capacity);
+                        for pair in table.windows(2) {
+                            prop_assert_ne!(rank_order(pair[0], pair[1]), Ordering::Greater);
+                            if pair[0].score == pair[1].score {
+                                prop_assert!(pair[0].date <= pair[1].date);
+                            }
+                        }
+                        for (index, entry) in table.iter().enumerate() {
+                            prop_assert_eq!(leaderboard.rank(entry), Some(index + 1));
+                        }
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn keeps_the_best_runs_of_every_table(capacity in 1..8usize, runs in runs()) {
+            let mut leaderboard = Leaderboard::new(capacity);
+            for run in &runs {
+                leaderboard.insert(run.clone());
+            }
+            for difficulty in Difficulty::ALL {
+                // The sort is stable, so runs that rank the same stay in the order they were added
+                let mut best: Vec<_> = runs
+                    .iter()
+                    .filter(|run| run.difficulty == difficulty)
+                    .collect();
+                best.sort_by(|a, b| rank_order(a, b));
+                best.truncate(capacity);
+                let table: Vec<_> = leaderboard.table(difficulty, Arena::Desert).collect();
+                prop_assert_eq!(table, best);
+            }
+        }
+    }
+}
@@ -1,19 +1,34 @@
 use bevy::app::AppExit;
 use bevy::prelude::*;
-use bevy_simple_text_input::{
-    TextInputBundle, TextInputInactive, TextInputPlugin, TextInputSubmitEvent,
-};
+use bevy_simple_text_input::{TextInputInactive, TextInputPlugin};
 
-use crate::loading::TextureAssets;
 pub use crate::menu::leaderboard::Leaderboard;
 use crate::menu::leaderboard::NameText;
 pub use crate::menu::leaderboard::Score;
 use crate::player::Death;
+use crate::profile::Profiles;
+use crate::settings::{Language, Settings};
 use crate::GameState;
 
-use self::leaderboard::PlayerName;
+use self::achievements::AchievementsMenuPlugin;
+use self::characters::CharactersMenuPlugin;
+use self::credits::CreditsMenuPlugin;
+use self::leaderboard::LeaderboardMenuPlugin;
+use self::navigation::{Focusable, MenuActivated, MenuBack, MenuNavigationPlugin};
+use self::profiles::ProfilesMenuPlugin;
+use self::run_stats::RunStatsMenuPlugin;
+use self::settings::{Setting, SettingsMenuPlugin};
+use self::shop::ShopMenuPlugin;
 
+mod achievements;
+mod characters;
+mod credits;
 pub mod leaderboard;
+mod navigation;
+mod profiles;
+mod run_stats;
+mod settings;
+mod shop;
 pub struct MenuPlugin;
 
 const BORDER_COLOR_ACTIVE: Color = Color::VIOLET;
@@ -24,15 +39,42 @@ const TEXT_COLOR: Color = Color::WHITE;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Menu), setup_menu)
-            .init_resource::<Score>()
-            .init_resource::<PlayerName>()
-            .insert_resource(Leaderboard::default())
-            .add_systems(Update, menu_action.run_if(in_state(GameState::Menu)))
-            .add_systems(OnExit(GameState::Menu), cleanup_menu)
-            .add_systems(Update, set_name.run_if(in_state(GameState::Menu)))
-            .add_systems(Update, focus.run_if(in_state(GameState::Menu)))
-            .add_plugins(TextInputPlugin);
+        app.add_systems(
+            OnEnter(GameState::Menu),
+            (setup_menu_camera, store_death_message, open_main_menu),
+        )
+        .init_resource::<Score>()
+        .init_resource::<MenuStack>()
+        .init_resource::<DeathMessage>()
+        .insert_resource(Leaderboard::load())
+        .add_systems(OnEnter(MenuScreen::Main), setup_main_menu)
+        .add_systems(OnExit(MenuScreen::Main), cleanup_screen::<MainMenu>)
+        .add_systems(
+            Update,
+            (
+                menu_action,
+                button_colors,
+                update_name_text,
+                focus,
+                style_text_input,
+            )
+                .run_if(in_state(GameState::Menu)),
+        )
+        .add_systems(Update, translate_text.run_if(in_state(GameState::Menu)))
+        .add_systems(OnExit(GameState::Menu), close_menu)
+        .init_state::<MenuScreen>()
+        .add_plugins((
+            TextInputPlugin,
+            MenuNavigationPlugin,
+            SettingsMenuPlugin,
+            LeaderboardMenuPlugin,
+            CharactersMenuPlugin,
+            ProfilesMenuPlugin,
+            ShopMenuPlugin,
+            RunStatsMenuPlugin,
+            AchievementsMenuPlugin,
+            CreditsMenuPlugin,
+        ));
     }
 }
 
@@ -51,12 +93,41 @@ impl Default for ButtonColors {
     }
 }
 
+/// Which screen of the menu is shown, only ever `Closed` outside of [`GameState::Menu`]
+#[derive(States, Default, Clone, Copy, Eq, PartialEq, Debug, Hash)]
+enum MenuScreen {
+    #[default]
+    Closed,
+    Main,
+    Characters,
+    Settings,
+    Leaderboard,
+    Profiles,
+    Shop,
+    Achievements,
+    Credits,
+}
+
+/// The screens to return to when going back, the most recent one last
+#[derive(Resource, Default)]
+struct MenuStack(Vec<MenuScreen>);
+
+/// Why the last run ended, shown at the bottom of the main screen
+#[derive(Resource, Default)]
+struct DeathMessage(Option<String>);
+
+#[derive(Component)]
+struct MainMenu;
+
+/// Text that is shown in the chosen language, holds the English original
 #[derive(Component)]
-struct Menu;
+struct TranslatedText(&'static str);
 
 #[derive(Component)]
 enum MenuButtonAction {
-    Play,
+    Open(MenuScreen),
+    ChangeSetting(Setting),
+    Back,
     Quit,
     OpenLink,
 }
@@ -64,37 +135,79 @@ enum MenuButtonAction {
 #[derive(Component)]
 pub struct MainCamera;
 
-fn setup_menu(
-    mut commands: Commands,
-    textures: Res<TextureAssets>,
-    leaderboard: Res<Leaderboard>,
-    mut message: EventReader<Death>,
-    mut evr_char: EventReader<ReceivedCharacter>,
-    kbd: Res<ButtonInput<KeyCode>>,
-    mut string: Local<String>,
+#[derive(Component)]
+struct OpenLink(&'static str);
+
+/// Spawns a focusable button with a translated label
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    label: &'static str,
+    action: MenuButtonAction,
+    style: Style,
+    text_style: TextStyle,
+    language: Language,
 ) {
-    if kbd.just_pressed(KeyCode::Enter) {
-        string.clear();
-    }
-    if kbd.just_pressed(KeyCode::Backspace) {
-        string.pop();
-    }
-    for ev in evr_char.read() {
-        if !ev
-            .char
-            .chars()
-            .last()
-            .expect("Invalid char entered")
-            .is_control()
-        {
-            string.push(ev.char.chars().last().expect("Invalid character entered"));
-        }
+    let button_colors = ButtonColors::default();
+    parent
+        .spawn((
+            ButtonBundle {
+                style,
+                background_color: button_colors.normal.into(),
+                ..Default::default()
+            },
+            button_colors,
+            action,
+            Focusable,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(language.tr(label), text_style),
+                TranslatedText(label),
+            ));
+        });
+}
+
+/// A full screen column with everything centered, the root of most menu screens
+fn screen_root() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        ..default()
     }
+}
+
+fn setup_menu_camera(mut commands: Commands) {
+    commands.spawn((Camera2dBundle::default(), MainCamera));
+}
+
+fn store_death_message(mut message: ResMut<DeathMessage>, mut death_events: EventReader<Death>) {
+    message.0 = death_events
+        .read()
+        .last()
+        .map(|death| death.message.clone());
+}
 
+fn open_main_menu(mut next_screen: ResMut<NextState<MenuScreen>>) {
+    next_screen.set(MenuScreen::Main);
+}
+
+fn setup_main_menu(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    message: Res<DeathMessage>,
+    leaderboard: Res<Leaderboard>,
+) {
     let button_style = Style {
-        width: Val::Px(200.0),
+        width: Val::Px(250.0),
         height: Val::Px(50.0),
-        margin: UiRect::all(Val::Px(20.0)),
+        margin: UiRect::all(Val::Px(10.0)),
         align_items: AlignItems::Center,
         justify_content: JustifyContent::Center,
         ..default()
@@ -106,221 +219,37 @@ fn setup_menu(
         ..default()
     };
 
-    commands.spawn((Camera2dBundle::default(), MainCamera));
     commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    align_items: AlignItems::Center,
-                    justify_content: JustifyContent::Center,
-                    ..default()
-                },
-                ..default()
-            },
-            Interaction::None,
-            Menu,
-        ))
+        .spawn((screen_root(), Interaction::None, MainMenu))
         .with_children(|parent| {
-            parent
-                .spawn(NodeBundle {
-                    style: Style {
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::Center,
-                        ..default()
-                    },
-                    ..default()
-                })
-                .with_children(|parent| {
-                    parent.spawn((
-                        NodeBundle {
-                            style: Style {
-                                width: Val::Px(250.0),
-                                border: UiRect::all(Val::Px(5.0)),
-                                padding: UiRect::all(Val::Px(5.0)),
-                                ..default()
-                            },
-                            border_color: BorderColor(BORDER_COLOR_ACTIVE),
-                            background_color: Color::DARK_GRAY.into(),
-                            ..default()
-                        },
-                        TextInputBundle::default()
-                            .with_text_style(TextStyle {
-                                font_size: 30.,
-                                color: Color::rgb(0.9, 0.9, 0.9),
-                                ..default()
-                            })
-                            .with_placeholder("Enter name..", None)
-                            .with_inactive(true),
-                    ));
-                    // Place flex stuff here
-                    let button_colors = ButtonColors::default();
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style.clone(),
-                                background_color: button_colors.normal.clone().into(),
-                                ..Default::default()
-                            },
-                            button_colors,
-                            MenuButtonAction::Play,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Play".to_string(),
-                                button_text_style.clone(),
-                            ));
-                        });
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style,
-                                background_color: button_colors.normal.clone().into(),
-                                ..Default::default()
-                            },
-                            button_colors,
-                            MenuButtonAction::Quit,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Quit".to_string(),
-                                button_text_style,
-                            ));
-                        });
-                });
-        });
-    commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    flex_direction: FlexDirection::Column,
-                    align_items: AlignItems::End,
-                    justify_content: JustifyContent::Start,
-                    left: Val::Px(20.),
-                    height: Val::Percent(80.),
-                    position_type: PositionType::Absolute,
-                    ..default()
-                },
-                ..default()
-            },
-            Menu,
-        ))
-        .with_children(|children| {
-            for (name, score) in &leaderboard.leaderboard {
-                if score.score > 0 {
-                    children.spawn({
-                        TextBundle::from_section(
-                            format!("{}: {}", name.0, score.score),
-                            TextStyle {
-                                font_size: 18.0,
-                                color: Color::rgb(0.9, 0.9, 0.9),
-                                ..default()
-                            },
-                        )
-                    });
-                }
+            for (label, action) in [
+                ("Play", MenuButtonAction::Open(MenuScreen::Characters)),
+                ("Profiles", MenuButtonAction::Open(MenuScreen::Profiles)),
+                ("Shop", MenuButtonAction::Open(MenuScreen::Shop)),
+                ("Settings", MenuButtonAction::Open(MenuScreen::Settings)),
+                (
+                    "Leaderboard",
+                    MenuButtonAction::Open(MenuScreen::Leaderboard),
+                ),
+                (
+                    "Achievements",
+                    MenuButtonAction::Open(MenuScreen::Achievements),
+                ),
+                ("Credits", MenuButtonAction::Open(MenuScreen::Credits)),
+                ("Quit", MenuButtonAction::Quit),
+            ] {
+                spawn_button(
+                    parent,
+                    label,
+                    action,
+                    button_style.clone(),
+                    button_text_style.clone(),
+                    settings.language,
+                );
             }
         });
 
-    if message.is_empty() {
-        commands
-            .spawn((
-                NodeBundle {
-                    style: Style {
-                        flex_direction: FlexDirection::Row,
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::SpaceAround,
-                        bottom: Val::Px(5.),
-                        width: Val::Percent(100.),
-                        position_type: PositionType::Absolute,
-                        ..default()
-                    },
-                    ..default()
-                },
-                Menu,
-            ))
-            .with_children(|children| {
-                children
-                    .spawn((
-                        ButtonBundle {
-                            style: Style {
-                                width: Val::Px(170.0),
-                                height: Val::Px(50.0),
-                                align_items: AlignItems::Center,
-                                justify_content: JustifyContent::SpaceAround,
-                                padding: UiRect::all(Val::Px(5.)),
-                                ..Default::default()
-                            },
-                            background_color: Color::NONE.into(),
-                            ..Default::default()
-                        },
-                        ButtonColors {
-                            normal: Color::NONE,
-                            ..default()
-                        },
-                        OpenLink("https://bevyengine.org"),
-                        MenuButtonAction::OpenLink,
-                    ))
-                    .with_children(|parent| {
-                        parent.spawn(TextBundle::from_section(
-                            "Made with Bevy",
-                            TextStyle {
-                                font_size: 15.0,
-                                color: Color::rgb(0.9, 0.9, 0.9),
-                                ..default()
-                            },
-                        ));
-                        parent.spawn(ImageBundle {
-                            image: textures.bevy.clone().into(),
-                            style: Style {
-                                width: Val::Px(32.),
-                                ..default()
-                            },
-                            ..default()
-                        });
-                    });
-                children
-                    .spawn((
-                        ButtonBundle {
-                            style: Style {
-                                width: Val::Px(170.0),
-                                height: Val::Px(50.0),
-                                justify_content: JustifyContent::SpaceAround,
-                                align_items: AlignItems::Center,
-                                padding: UiRect::all(Val::Px(5.)),
-                                ..default()
-                            },
-                            background_color: Color::NONE.into(),
-                            ..Default::default()
-                        },
-                        ButtonColors {
-                            normal: Color::NONE,
-                            hovered: Color::rgb(0.25, 0.25, 0.25),
-                        },
-                        OpenLink("https://github.com/idjotherwise"),
-                        MenuButtonAction::OpenLink,
-                    ))
-                    .with_children(|parent| {
-                        parent.spawn(TextBundle::from_section(
-                            "Source",
-                            TextStyle {
-                                font_size: 15.0,
-                                color: Color::rgb(0.9, 0.9, 0.9),
-                                ..default()
-                            },
-                        ));
-                        parent.spawn(ImageBundle {
-                            image: textures.bevy.clone().into(),
-                            style: Style {
-                                width: Val::Px(32.),
-                                ..default()
-                            },
-                            ..default()
-                        });
-                    });
-            });
-    } else {
+    if let Some(message) = &message.0 {
         commands
             .spawn((
                 NodeBundle {
@@ -335,21 +264,29 @@ fn setup_menu(
                     },
                     ..default()
                 },
-                Menu,
+                MainMenu,
             ))
             .with_children(|children| {
                 children.spawn(TextBundle::from_section(
-                    message.read().next().unwrap().message.to_string(),
+                    message.clone(),
                     TextStyle {
                         font_size: 15.0,
                         color: Color::rgb(0.9, 0.9, 0.9),
                         ..default()
                     },
                 ));
+                if let Some(rank) = leaderboard.latest_rank() {
+                    children.spawn(TextBundle::from_section(
+                        format!("{} #{rank}", settings.language.tr("Leaderboard rank")),
+                        TextStyle {
+                            font_size: 15.0,
+                            color: Color::GOLD,
+                            ..default()
+                        },
+                    ));
+                }
             });
-
-        message.clear();
-    };
+    }
     commands.spawn((
         // Create a TextBundle that has a Text with a list of sections.
         TextBundle::from_sections([
@@ -361,121 +298,180 @@ fn setup_menu(
                     ..default()
                 },
             ),
-            TextSection::from_style(TextStyle {
-                font_size: 40.0,
-                color: Color::GOLD,
-                // If no font is specified, the default font (a minimal subset of FiraMono) will be used.
-                ..default()
-            }),
+            TextSection::new(
+                player_name(&profiles, settings.language),
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::GOLD,
+                    // If no font is specified, the default font (a minimal subset of FiraMono) will be used.
+                    ..default()
+                },
+            ),
         ]),
         NameText,
-        Menu,
+        MainMenu,
     ));
 }
 
-#[derive(Component)]
-struct OpenLink(&'static str);
+/// Runs are recorded under the picked profile, or anonymously until one is picked
+fn player_name(profiles: &Profiles, language: Language) -> String {
+    profiles.current().map_or_else(
+        || language.tr("Anonymous").to_string(),
+        |profile| profile.name.clone(),
+    )
+}
 
-fn set_name(
-    mut events: EventReader<TextInputSubmitEvent>,
-    mut name: ResMut<PlayerName>,
-    mut q_name_text: Query<&mut Text, With<NameText>>,
-    mut text_input_query: Query<(
-        &mut TextInputInactive,
-        &mut BorderColor,
-        &mut BackgroundColor,
-    )>,
+fn update_name_text(
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut name_text_query: Query<&mut Text, With<NameText>>,
 ) {
-    for event in events.read() {
-        info!("{:?} Setting name to: {}", event.entity, event.value);
-        name.set(&event.value);
-        for mut text in &mut q_name_text {
-            text.sections[1].value = format!("{}", name.0)
-        }
-        for (mut inactive, mut border_color, mut background_color) in &mut text_input_query {
-            inactive.0 = true;
-            *border_color = BORDER_COLOR_INACTIVE.into();
-            *background_color = BACKGROUND_COLOR_INACTIVE.into();
-        }
+    if !profiles.is_changed() && !settings.is_changed() {
+        return;
+    }
+    for mut text in &mut name_text_query {
+        text.sections[1].value = player_name(&profiles, settings.language);
     }
 }
 
+/// Activating the name field starts typing in it, activating anything else stops
 fn focus(
-    query: Query<(Entity, &Interaction), Changed<Interaction>>,
-    mut text_input_query: Query<(
-        Entity,
-        &mut TextInputInactive,
-        &mut BorderColor,
-        &mut BackgroundColor,
-    )>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut activated_events: EventReader<MenuActivated>,
+    mut pending: Local<Option<Entity>>,
+    mut text_input_query: Query<(Entity, &mut TextInputInactive)>,
 ) {
-    for (interaction_entity, interaction) in &query {
-        if *interaction == Interaction::Pressed {
-            for (entity, mut inactive, mut border_color, mut background_color) in
-                &mut text_input_query
-            {
-                if entity == interaction_entity {
-                    inactive.0 = false;
-                    *border_color = BORDER_COLOR_ACTIVE.into();
-                    *background_color = BACKGROUND_COLOR_ACTIVE.into();
-                } else {
-                    inactive.0 = true;
-                    *border_color = BORDER_COLOR_INACTIVE.into();
-                    *background_color = BACKGROUND_COLOR_INACTIVE.into();
-                }
-            }
-        }
+    if let Some(MenuActivated(entity)) = activated_events.read().last() {
+        *pending = Some(*entity);
+    }
+    // The text field would pick up the key that activated it, so wait until it is let go
+    let keys = [KeyCode::Enter, KeyCode::Space];
+    if keyboard_input.any_pressed(keys) || keyboard_input.any_just_released(keys) {
+        return;
+    }
+    let Some(activated) = pending.take() else {
+        return;
+    };
+    for (entity, mut inactive) in &mut text_input_query {
+        inactive.0 = entity != activated;
     }
 }
 
-fn menu_action(
-    mut next_state: ResMut<NextState<GameState>>,
-    touch: Res<Touches>,
-    mut interaction_query: Query<
+fn style_text_input(
+    mut text_input_query: Query<
         (
-            &Interaction,
+            Ref<TextInputInactive>,
+            &mut BorderColor,
             &mut BackgroundColor,
-            &ButtonColors,
-            Option<&OpenLink>,
-            &MenuButtonAction,
         ),
-        (Changed<Interaction>, With<Button>),
+        Changed<TextInputInactive>,
     >,
+) {
+    for (inactive, mut border_color, mut background_color) in &mut text_input_query {
+        if inactive.is_added() {
+            continue;
+        }
+        if inactive.0 {
+            *border_color = BORDER_COLOR_INACTIVE.into();
+            *background_color = BACKGROUND_COLOR_INACTIVE.into();
+        } else {
+            *border_color = BORDER_COLOR_ACTIVE.into();
+            *background_color = BACKGROUND_COLOR_ACTIVE.into();
+        }
+    }
+}
+
+fn go_back(
+    stack: &mut MenuStack,
+    screen: &State<MenuScreen>,
+    next_screen: &mut NextState<MenuScreen>,
+) {
+    if *screen.get() == MenuScreen::Main {
+        return;
+    }
+    next_screen.set(stack.0.pop().unwrap_or(MenuScreen::Main));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn menu_action(
+    mut next_screen: ResMut<NextState<MenuScreen>>,
+    screen: Res<State<MenuScreen>>,
+    mut stack: ResMut<MenuStack>,
+    mut settings: ResMut<Settings>,
+    action_query: Query<(&MenuButtonAction, Option<&OpenLink>)>,
+    mut activated_events: EventReader<MenuActivated>,
+    mut back_events: EventReader<MenuBack>,
     mut app_exit_events: EventWriter<AppExit>,
 ) {
-    if touch.first_pressed_position().is_some() {
-        next_state.set(GameState::Playing);
-    };
-    for (interaction, mut color, button_colors, open_link, action) in &mut interaction_query {
-        match *interaction {
-            Interaction::Pressed => match *action {
-                MenuButtonAction::Play => {
-                    next_state.set(GameState::Playing);
-                }
-                MenuButtonAction::Quit => {
-                    app_exit_events.send(AppExit);
-                }
+    if back_events.read().count() > 0 {
+        go_back(&mut stack, &screen, &mut next_screen);
+    }
+    for MenuActivated(entity) in activated_events.read() {
+        let Ok((action, open_link)) = action_query.get(*entity) else {
+            continue;
+        };
+        match *action {
+            MenuButtonAction::Open(next) => {
+                stack.0.push(*screen.get());
+                next_screen.set(next);
+            }
+            MenuButtonAction::ChangeSetting(setting) => {
+                setting.change(&mut settings);
+            }
+            MenuButtonAction::Back => {
+                go_back(&mut stack, &screen, &mut next_screen);
+            }
+            MenuButtonAction::Quit => {
+                app_exit_events.send(AppExit);
+            }
 
-                MenuButtonAction::OpenLink => {
-                    if let Some(link) = open_link {
-                        if let Err(error) = webbrowser::open(link.0) {
-                            warn!("Failed to open link {error:?}");
-                        }
+            MenuButtonAction::OpenLink => {
+                if let Some(link) = open_link {
+                    if let Err(error) = webbrowser::open(link.0) {
+                        warn!("Failed to open link {error:?}");
                     }
                 }
-            },
-            Interaction::Hovered => {
-                *color = button_colors.hovered.into();
-            }
-            Interaction::None => {
-                *color = button_colors.normal.into();
             }
         }
     }
 }
 
-fn cleanup_menu(mut commands: Commands, menu: Query<Entity, With<Menu>>) {
-    for entity in menu.iter() {
+fn button_colors(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &ButtonColors),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    for (interaction, mut color, button_colors) in &mut interaction_query {
+        *color = match *interaction {
+            Interaction::Pressed | Interaction::Hovered => button_colors.hovered.into(),
+            Interaction::None => button_colors.normal.into(),
+        };
+    }
+}
+
+fn translate_text(settings: Res<Settings>, mut text_query: Query<(&mut Text, &TranslatedText)>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut text, TranslatedText(english)) in &mut text_query {
+        text.sections[0].value = settings.language.tr(english).to_string();
+    }
+}
+
+fn close_menu(
+    mut next_screen: ResMut<NextState<MenuScreen>>,
+    mut stack: ResMut<MenuStack>,
+    mut message: ResMut<DeathMessage>,
+) {
+    next_screen.set(MenuScreen::Closed);
+    stack.0.clear();
+    message.0 = None;
+}
+
+/// Despawns the root entities of a menu screen when leaving it
+fn cleanup_screen<T: Component>(mut commands: Commands, screen_query: Query<Entity, With<T>>) {
+    for entity in screen_query.iter() {
         commands.entity(entity).despawn_recursive();
     }
 }
@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+use bevy_simple_text_input::{TextInputInactive, TextInputSubmitEvent};
+
+use crate::GameState;
+
+const FOCUS_RING_COLOR: Color = Color::GOLD;
+
+pub struct MenuNavigationPlugin;
+
+/// Can be reached with the arrow keys or the D-pad
+#[derive(Component)]
+pub struct Focusable;
+
+/// The element that Enter or the A button activates, moved by hovering as well
+#[derive(Resource, Default)]
+pub struct MenuFocus(pub Option<Entity>);
+
+/// An element was clicked, tapped or activated with the keyboard or a gamepad
+#[derive(Event)]
+pub struct MenuActivated(pub Entity);
+
+/// Escape or the B button was pressed
+#[derive(Event)]
+pub struct MenuBack;
+
+impl Plugin for MenuNavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuFocus>()
+            .add_event::<MenuActivated>()
+            .add_event::<MenuBack>()
+            .add_systems(
+                Update,
+                (
+                    add_focus_ring,
+                    pointer_input,
+                    navigate_menu,
+                    show_focus_ring,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Menu)),
+            )
+            .add_systems(OnExit(GameState::Menu), clear_focus);
+    }
+}
+
+fn add_focus_ring(mut commands: Commands, focusable_query: Query<Entity, Added<Focusable>>) {
+    for entity in &focusable_query {
+        commands
+            .entity(entity)
+            .insert(Outline::new(Val::Px(3.), Val::Px(2.), Color::NONE));
+    }
+}
+
+/// Mouse clicks and taps on touch screens both press the button under them
+fn pointer_input(
+    mut focus: ResMut<MenuFocus>,
+    interaction_query: Query<(Entity, &Interaction, Has<Focusable>), Changed<Interaction>>,
+    mut activated_events: EventWriter<MenuActivated>,
+) {
+    for (entity, interaction, focusable) in &interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                // Touch screens have no hover, so the tapped button takes the focus here
+                if focusable {
+                    focus.0 = Some(entity);
+                }
+                activated_events.send(MenuActivated(entity));
+            }
+            Interaction::Hovered if focusable => focus.0 = Some(entity),
+            _ => {}
+        }
+    }
+}
+
+/// Picks the closest element in the given direction, preferring ones that are in line with the current one
+fn next_in_direction(
+    current: Vec2,
+    direction: Vec2,
+    candidates: impl Iterator<Item = (Entity, Vec2)>,
+) -> Option<Entity> {
+    candidates
+        .filter_map(|(entity, position)| {
+            let offset = position - current;
+            let along = offset.dot(direction);
+            if along <= 1. {
+                return None;
+            }
+            let across = offset.perp_dot(direction).abs();
+            Some((entity, along + across * 2.))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn navigate_menu(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    mut focus: ResMut<MenuFocus>,
+    focusable_query: Query<(Entity, &GlobalTransform, &InheritedVisibility), With<Focusable>>,
+    mut text_input_query: Query<&mut TextInputInactive>,
+    mut submit_events: EventReader<TextInputSubmitEvent>,
+    mut activated_events: EventWriter<MenuActivated>,
+    mut back_events: EventWriter<MenuBack>,
+) {
+    let gamepad_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    let back =
+        keyboard_input.just_pressed(KeyCode::Escape) || gamepad_pressed(GamepadButtonType::East);
+
+    // The keys belong to the text field while typing, Enter there submits the name
+    let submitted = submit_events.read().count() > 0;
+    if submitted || text_input_query.iter().any(|inactive| !inactive.0) {
+        if back {
+            for mut inactive in &mut text_input_query {
+                inactive.0 = true;
+            }
+        }
+        return;
+    }
+
+    if back {
+        back_events.send(MenuBack);
+        return;
+    }
+
+    let focusables = || {
+        focusable_query
+            .iter()
+            .filter(|(_, _, visibility)| visibility.get())
+            .map(|(entity, transform, _)| (entity, transform.translation().truncate()))
+    };
+    let current = focus
+        .0
+        .and_then(|entity| focusables().find(|(focused, _)| *focused == entity));
+    if current.is_none() {
+        focus.0 = None;
+    }
+
+    if keyboard_input.any_just_pressed([KeyCode::Enter, KeyCode::Space])
+        || gamepad_pressed(GamepadButtonType::South)
+    {
+        if let Some((entity, _)) = current {
+            activated_events.send(MenuActivated(entity));
+        }
+        return;
+    }
+
+    // UI coordinates grow downwards
+    let direction = if keyboard_input.just_pressed(KeyCode::ArrowUp)
+        || gamepad_pressed(GamepadButtonType::DPadUp)
+    {
+        Vec2::NEG_Y
+    } else if keyboard_input.just_pressed(KeyCode::ArrowDown)
+        || gamepad_pressed(GamepadButtonType::DPadDown)
+    {
+        Vec2::Y
+    } else if keyboard_input.just_pressed(KeyCode::ArrowLeft)
+        || gamepad_pressed(GamepadButtonType::DPadLeft)
+    {
+        Vec2::NEG_X
+    } else if keyboard_input.just_pressed(KeyCode::ArrowRight)
+        || gamepad_pressed(GamepadButtonType::DPadRight)
+    {
+        Vec2::X
+    } else {
+        return;
+    };
+
+    focus.0 = match current {
+        Some((entity, position)) => {
+            next_in_direction(position, direction, focusables()).or(Some(entity))
+        }
+        // The first press only shows where the focus is, starting from the top
+        None => focusables()
+            .min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)))
+            .map(|(entity, _)| entity),
+    };
+}
+
+fn show_focus_ring(
+    focus: Res<MenuFocus>,
+    mut outline_query: Query<(Entity, &mut Outline), With<Focusable>>,
+) {
+    for (entity, mut outline) in &mut outline_query {
+        let color = if focus.0 == Some(entity) {
+            FOCUS_RING_COLOR
+        } else {
+            Color::NONE
+        };
+        if outline.color != color {
+            outline.color = color;
+        }
+    }
+}
+
+fn clear_focus(mut focus: ResMut<MenuFocus>) {
+    focus.0 = None;
+}
@@ -0,0 +1,314 @@
+use bevy::prelude::*;
+use bevy_simple_text_input::{
+    TextInputBundle, TextInputInactive, TextInputSettings, TextInputSubmitEvent, TextInputValue,
+};
+
+use crate::profile::{validate_name, NameError, Profile, Profiles};
+use crate::run::Arena;
+use crate::settings::Settings;
+
+use super::leaderboard::format_time;
+use super::navigation::{Focusable, MenuActivated};
+use super::{
+    cleanup_screen, screen_root, spawn_button, ButtonColors, MenuButtonAction, MenuScreen,
+    TranslatedText, BORDER_COLOR_ACTIVE, TEXT_COLOR,
+};
+
+const PROFILE_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const CURRENT_PROFILE_COLOR: Color = Color::GOLD;
+const ERROR_COLOR: Color = Color::rgb(1., 0.4, 0.4);
+
+pub struct ProfilesMenuPlugin;
+
+#[derive(Component)]
+struct ProfilesMenu;
+
+/// Holds one button per profile
+#[derive(Component)]
+struct ProfileList;
+
+/// Picks the profile at this index of [`Profiles::all`]
+#[derive(Component)]
+struct ProfileButton(usize);
+
+/// Explains why the typed name was not accepted
+#[derive(Component)]
+struct NameErrorText;
+
+impl Plugin for ProfilesMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuScreen::Profiles), setup_profiles_menu)
+            .add_systems(
+                Update,
+                (check_name, submit_name, select_profile, update_profile_list)
+                    .chain()
+                    .run_if(in_state(MenuScreen::Profiles)),
+            )
+            .add_systems(OnExit(MenuScreen::Profiles), cleanup_screen::<ProfilesMenu>);
+    }
+}
+
+fn setup_profiles_menu(mut commands: Commands, settings: Res<Settings>, profiles: Res<Profiles>) {
+    let language = settings.language;
+    commands
+        .spawn((screen_root(), ProfilesMenu))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    language.tr("Profiles"),
+                    TextStyle {
+                        font_size: 40.0,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                }),
+                TranslatedText("Profiles"),
+            ));
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            margin: UiRect::bottom(Val::Px(10.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    ProfileList,
+                ))
+                .with_children(|parent| spawn_profile_list(parent, &profiles, &settings));
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(300.0),
+                        border: UiRect::all(Val::Px(5.0)),
+                        padding: UiRect::all(Val::Px(5.0)),
+                        ..default()
+                    },
+                    border_color: BorderColor(BORDER_COLOR_ACTIVE),
+                    background_color: Color::DARK_GRAY.into(),
+                    ..default()
+                },
+                TextInputBundle::default()
+                    .with_text_style(TextStyle {
+                        font_size: 30.,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                        ..default()
+                    })
+                    .with_placeholder(language.tr("New profile..."), None)
+                    .with_inactive(true)
+                    // Kept after a rejected name so it can be fixed
+                    .with_settings(TextInputSettings {
+                        retain_on_submit: true,
+                        ..default()
+                    }),
+                Focusable,
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 18.0,
+                        color: ERROR_COLOR,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                }),
+                NameErrorText,
+            ));
+            spawn_button(
+                parent,
+                "Back",
+                MenuButtonAction::Back,
+                Style {
+                    width: Val::Px(250.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                TextStyle {
+                    font_size: 30.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+                language,
+            );
+        });
+}
+
+/// Explains what is wrong with the name while it is typed, but not that it is still empty
+fn check_name(
+    settings: Res<Settings>,
+    value_query: Query<&TextInputValue, Changed<TextInputValue>>,
+    mut error_query: Query<&mut Text, With<NameErrorText>>,
+) {
+    for value in &value_query {
+        let message = match validate_name(&value.0) {
+            Err(NameError::Empty) | Ok(_) => "",
+            Err(error) => settings.language.tr(error.message()),
+        };
+        for mut text in &mut error_query {
+            if text.sections[0].value != message {
+                text.sections[0].value = message.to_string();
+            }
+        }
+    }
+}
+
+fn submit_name(
+    settings: Res<Settings>,
+    mut profiles: ResMut<Profiles>,
+    mut submit_events: EventReader<TextInputSubmitEvent>,
+    mut text_input_query: Query<(&mut TextInputValue, &mut TextInputInactive)>,
+    mut error_query: Query<&mut Text, With<NameErrorText>>,
+) {
+    for event in submit_events.read() {
+        let error = profiles.select_or_create(&event.value).err();
+        for mut text in &mut error_query {
+            text.sections[0].value = error
+                .map(|error| settings.language.tr(error.message()))
+                .unwrap_or_default()
+                .to_string();
+        }
+        if error.is_none() {
+            for (mut value, mut inactive) in &mut text_input_query {
+                value.0.clear();
+                inactive.0 = true;
+            }
+        }
+    }
+}
+
+fn select_profile(
+    mut profiles: ResMut<Profiles>,
+    button_query: Query<&ProfileButton>,
+    mut activated_events: EventReader<MenuActivated>,
+) {
+    for MenuActivated(entity) in activated_events.read() {
+        if let Ok(ProfileButton(index)) = button_query.get(*entity) {
+            profiles.select(*index);
+        }
+    }
+}
+
+/// The name of a profile with the totals of its runs
+fn profile_label(profile: &Profile, settings: &Settings) -> String {
+    let language = settings.language;
+    let best = profile
+        .best_score(settings.difficulty, Arena::default())
+        .map_or_else(|| "-".to_string(), |score| score.to_string());
+    format!(
+        "{}   {} ({}): {best}   {}: {}   {}: {}   {}: {}",
+        profile.name,
+        language.tr("Best score"),
+        language.tr(settings.difficulty.name()),
+        language.tr("Runs"),
+        profile.runs,
+        language.tr("Kills"),
+        profile.kills,
+        language.tr("Time"),
+        format_time(profile.time_played),
+    )
+}
+
+fn profile_text_style(current: bool) -> TextStyle {
+    TextStyle {
+        font_size: 20.0,
+        color: if current {
+            CURRENT_PROFILE_COLOR
+        } else {
+            PROFILE_COLOR
+        },
+        ..default()
+    }
+}
+
+fn spawn_profile_list(parent: &mut ChildBuilder, profiles: &Profiles, settings: &Settings) {
+    if profiles.all().is_empty() {
+        parent.spawn(TextBundle::from_section(
+            settings.language.tr("No profiles yet"),
+            profile_text_style(false),
+        ));
+    }
+    let current = profiles.current().map(|profile| &profile.name);
+    for (index, profile) in profiles.all().iter().enumerate() {
+        let button_colors = ButtonColors::default();
+        parent
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        min_width: Val::Px(500.0),
+                        height: Val::Px(36.0),
+                        margin: UiRect::all(Val::Px(4.0)),
+                        padding: UiRect::horizontal(Val::Px(10.0)),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    background_color: button_colors.normal.into(),
+                    ..default()
+                },
+                button_colors,
+                ProfileButton(index),
+                Focusable,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    profile_label(profile, settings),
+                    profile_text_style(current == Some(&profile.name)),
+                ));
+            });
+    }
+}
+
+/// Rebuilds the list when profiles are added and relabels it otherwise, so the focus stays put
+fn update_profile_list(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    list_query: Query<Entity, With<ProfileList>>,
+    button_query: Query<(&ProfileButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    let Ok(list) = list_query.get_single() else {
+        return;
+    };
+    if !profiles.is_changed() && !settings.is_changed() {
+        return;
+    }
+    if button_query.iter().count() != profiles.all().len() {
+        commands.entity(list).despawn_descendants();
+        commands
+            .entity(list)
+            .with_children(|parent| spawn_profile_list(parent, &profiles, &settings));
+        return;
+    }
+    let current = profiles.current().map(|profile| &profile.name);
+    for (ProfileButton(index), children) in &button_query {
+        let Some(profile) = profiles.all().get(*index) else {
+            continue;
+        };
+        let Some(mut text) = children
+            .first()
+            .and_then(|child| text_query.get_mut(*child).ok())
+        else {
+            continue;
+        };
+        let label = profile_label(profile, &settings);
+        let style = profile_text_style(current == Some(&profile.name));
+        if text.sections[0].value != label || text.sections[0].style.color != style.color {
+            text.sections[0].value = label;
+            text.sections[0].style = style;
+        }
+    }
+}
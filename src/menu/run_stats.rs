@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+
+use crate::enemy::EnemyKind;
+use crate::item::Weapon;
+#[cfg(feature = "export")]
+use crate::run::ExportFormat;
+use crate::run::{RunFinished, RunReport};
+use crate::settings::{Language, Settings};
+use crate::upgrades::Upgrades;
+use crate::GameState;
+
+use super::leaderboard::format_time;
+#[cfg(feature = "export")]
+use super::navigation::{Focusable, MenuActivated};
+#[cfg(feature = "export")]
+use super::ButtonColors;
+use super::{cleanup_screen, MenuScreen, TranslatedText, TEXT_COLOR};
+
+const STAT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+
+pub struct RunStatsMenuPlugin;
+
+/// The run that just ended, shown on the main screen until the next one starts
+#[derive(Resource, Default)]
+struct LastRun(Option<RunReport>);
+
+#[derive(Component)]
+struct RunStatsPanel;
+
+#[cfg(feature = "export")]
+#[derive(Component)]
+struct ExportButton(ExportFormat);
+
+/// Says where the last export went
+#[cfg(feature = "export")]
+#[derive(Component)]
+struct ExportStatus;
+
+impl Plugin for RunStatsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LastRun>()
+            .add_systems(OnEnter(GameState::Menu), store_last_run)
+            .add_systems(OnEnter(MenuScreen::Main), setup_run_stats)
+            .add_systems(OnExit(MenuScreen::Main), cleanup_screen::<RunStatsPanel>)
+            .add_systems(OnExit(GameState::Menu), forget_last_run);
+
+        #[cfg(feature = "export")]
+        app.add_systems(Update, export_run.run_if(in_state(MenuScreen::Main)));
+    }
+}
+
+fn store_last_run(mut last_run: ResMut<LastRun>, mut finished_events: EventReader<RunFinished>) {
+    if let Some(RunFinished(report)) = finished_events.read().last() {
+        last_run.0 = Some(report.clone());
+    }
+}
+
+fn forget_last_run(mut last_run: ResMut<LastRun>) {
+    last_run.0 = None;
+}
+
+/// Label and value of every line in the panel
+fn stat_lines(report: &RunReport, language: Language) -> Vec<(&'static str, String)> {
+    let stats = &report.stats;
+    let mut lines = vec![
+        ("Time alive", format_time(stats.time_alive)),
+        ("Kills", stats.kills.to_string()),
+    ];
+    for kind in EnemyKind::ALL {
+        if let Some(kills) = stats.kills_by_enemy.get(&kind) {
+            lines.push((kind.name(), kills.to_string()));
+        }
+    }
+    for weapon in Weapon::ALL {
+        if let Some(kills) = stats.kills_by_weapon.get(&weapon) {
+            lines.push((weapon.name(), kills.to_string()));
+        }
+    }
+    lines.extend([
+        ("Damage dealt", format!("{:.0}", stats.damage_dealt)),
+        ("Damage taken", format!("{:.0}", stats.damage_taken)),
+        ("Distance", format!("{:.0}", stats.distance)),
+        ("Projectiles fired", stats.projectiles_fired.to_string()),
+        ("Experience", stats.experience.to_string()),
+        (
+            "Gold earned",
+            Upgrades::gold_for(report.entry.score).to_string(),
+        ),
+    ]);
+    lines
+        .into_iter()
+        .map(|(label, value)| (language.tr(label), value))
+        .collect()
+}
+
+fn setup_run_stats(mut commands: Commands, settings: Res<Settings>, last_run: Res<LastRun>) {
+    let Some(report) = &last_run.0 else {
+        return;
+    };
+    let language = settings.language;
+    let text_style = TextStyle {
+        font_size: 18.0,
+        color: STAT_COLOR,
+        ..default()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(20.0),
+                    top: Val::Px(20.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.5).into(),
+                ..default()
+            },
+            RunStatsPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    language.tr("Last run"),
+                    TextStyle {
+                        font_size: 24.0,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(5.0)),
+                    ..default()
+                }),
+                TranslatedText("Last run"),
+            ));
+            for (label, value) in stat_lines(report, language) {
+                parent.spawn(TextBundle::from_sections([
+                    TextSection::new(format!("{label}: "), text_style.clone()),
+                    TextSection::new(
+                        value,
+                        TextStyle {
+                            color: Color::GOLD,
+                            ..text_style.clone()
+                        },
+                    ),
+                ]));
+            }
+            #[cfg(feature = "export")]
+            spawn_export_buttons(parent, language, &text_style);
+        });
+}
+
+/// A button for every format and a line that says where the export went
+#[cfg(feature = "export")]
+fn spawn_export_buttons(parent: &mut ChildBuilder, language: Language, text_style: &TextStyle) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                margin: UiRect::top(Val::Px(10.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for (label, format) in [
+                ("Export JSON", ExportFormat::Json),
+                ("Export CSV", ExportFormat::Csv),
+            ] {
+                let button_colors = ButtonColors::default();
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(6.0)),
+                                margin: UiRect::right(Val::Px(6.0)),
+                                ..default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..default()
+                        },
+                        button_colors,
+                        ExportButton(format),
+                        Focusable,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section(language.tr(label), text_style.clone()),
+                            TranslatedText(label),
+                        ));
+                    });
+            }
+        });
+    parent.spawn((
+        TextBundle::from_section("", text_style.clone()),
+        ExportStatus,
+    ));
+}
+
+#[cfg(feature = "export")]
+fn export_run(
+    settings: Res<Settings>,
+    last_run: Res<LastRun>,
+    button_query: Query<&ExportButton>,
+    mut status_query: Query<&mut Text, With<ExportStatus>>,
+    mut activated_events: EventReader<MenuActivated>,
+) {
+    let Some(report) = &last_run.0 else {
+        return;
+    };
+    for MenuActivated(entity) in activated_events.read() {
+        let Ok(ExportButton(format)) = button_query.get(*entity) else {
+            continue;
+        };
+        let file = report.export(*format);
+        for mut text in &mut status_query {
+            text.sections[0].value = format!("{} {file}", settings.language.tr("Saved to"));
+        }
+    }
+}
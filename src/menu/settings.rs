@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+
+use crate::settings::{ColorPalette, Language, Settings, RESOLUTIONS, UI_SCALES};
+
+use super::navigation::Focusable;
+use super::{
+    cleanup_screen, screen_root, spawn_button, ButtonColors, MenuButtonAction, MenuScreen,
+    TEXT_COLOR,
+};
+
+pub struct SettingsMenuPlugin;
+
+/// One row of the settings screen, pressing it steps to the next value
+#[derive(Clone, Copy, Debug)]
+pub enum Setting {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Fullscreen,
+    Resolution,
+    UiScale,
+    VSync,
+    ScreenShake,
+    DamageNumbers,
+    HitFlash,
+    Palette,
+    Language,
+    Difficulty,
+    Minimap,
+    MinimapOpacity,
+}
+
+const SETTINGS: [Setting; 15] = [
+    Setting::Difficulty,
+    Setting::MasterVolume,
+    Setting::MusicVolume,
+    Setting::SfxVolume,
+    Setting::Fullscreen,
+    Setting::Resolution,
+    Setting::UiScale,
+    Setting::VSync,
+    Setting::ScreenShake,
+    Setting::DamageNumbers,
+    Setting::HitFlash,
+    Setting::Palette,
+    Setting::Minimap,
+    Setting::MinimapOpacity,
+    Setting::Language,
+];
+
+// Volumes go up in steps of this much and wrap around to 0 after 100%
+const VOLUME_STEP: f32 = 0.1;
+// The minimap opacity goes up in steps of this much and wraps around to the first step
+const OPACITY_STEP: f32 = 0.25;
+
+fn step_volume(volume: &mut f32) {
+    let next = ((*volume + VOLUME_STEP) / VOLUME_STEP).round() * VOLUME_STEP;
+    *volume = if next > 1. + VOLUME_STEP / 2. {
+        0.
+    } else {
+        next
+    };
+}
+
+impl Setting {
+    fn label(&self) -> &'static str {
+        match self {
+            Setting::MasterVolume => "Master volume",
+            Setting::MusicVolume => "Music volume",
+            Setting::SfxVolume => "Effects volume",
+            Setting::Fullscreen => "Window",
+            Setting::Resolution => "Resolution",
+            Setting::UiScale => "Interface size",
+            Setting::VSync => "VSync",
+            Setting::ScreenShake => "Screen shake",
+            Setting::DamageNumbers => "Damage numbers",
+            Setting::HitFlash => "Hit flash",
+            Setting::Palette => "Colours",
+            Setting::Language => "Language",
+            Setting::Difficulty => "Difficulty",
+            Setting::Minimap => "Minimap",
+            Setting::MinimapOpacity => "Minimap opacity",
+        }
+    }
+
+    fn value(&self, settings: &Settings) -> String {
+        let language = settings.language;
+        let toggle = |on: bool| language.tr(if on { "On" } else { "Off" }).to_string();
+        match self {
+            Setting::MasterVolume => format!("{:.0}%", settings.master_volume * 100.),
+            Setting::MusicVolume => format!("{:.0}%", settings.music_volume * 100.),
+            Setting::SfxVolume => format!("{:.0}%", settings.sfx_volume * 100.),
+            Setting::Fullscreen => language
+                .tr(if settings.fullscreen {
+                    "Fullscreen"
+                } else {
+                    "Windowed"
+                })
+                .to_string(),
+            Setting::Resolution => {
+                let (width, height) = settings.resolution();
+                format!("{width}x{height}")
+            }
+            Setting::UiScale => format!("{:.0}%", settings.ui_scale * 100.),
+            Setting::VSync => toggle(settings.vsync),
+            Setting::ScreenShake => toggle(settings.screen_shake),
+            Setting::DamageNumbers => toggle(settings.damage_numbers),
+            Setting::HitFlash => toggle(settings.hit_flash),
+            Setting::Palette => language
+                .tr(match settings.palette {
+                    ColorPalette::Default => "Default",
+                    ColorPalette::ColorblindSafe => "Colourblind",
+                })
+                .to_string(),
+            Setting::Language => settings.language.name().to_string(),
+            Setting::Difficulty => language.tr(settings.difficulty.name()).to_string(),
+            Setting::Minimap => language.tr(settings.minimap.name()).to_string(),
+            Setting::MinimapOpacity => format!("{:.0}%", settings.minimap_opacity * 100.),
+        }
+    }
+
+    pub fn change(&self, settings: &mut Settings) {
+        match self {
+            Setting::MasterVolume => step_volume(&mut settings.master_volume),
+            Setting::MusicVolume => step_volume(&mut settings.music_volume),
+            Setting::SfxVolume => step_volume(&mut settings.sfx_volume),
+            Setting::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            Setting::Resolution => {
+                settings.resolution = (settings.resolution + 1) % RESOLUTIONS.len();
+            }
+            Setting::UiScale => {
+                // A scale edited by hand starts over from the smallest one
+                let current = UI_SCALES
+                    .iter()
+                    .position(|scale| *scale == settings.ui_scale);
+                settings.ui_scale = current.map_or(UI_SCALES[0], |index| {
+                    UI_SCALES[(index + 1) % UI_SCALES.len()]
+                });
+            }
+            Setting::VSync => settings.vsync = !settings.vsync,
+            Setting::ScreenShake => settings.screen_shake = !settings.screen_shake,
+            Setting::DamageNumbers => settings.damage_numbers = !settings.damage_numbers,
+            Setting::HitFlash => settings.hit_flash = !settings.hit_flash,
+            Setting::Palette => {
+                settings.palette = match settings.palette {
+                    ColorPalette::Default => ColorPalette::ColorblindSafe,
+                    ColorPalette::ColorblindSafe => ColorPalette::Default,
+                }
+            }
+            Setting::Language => {
+                settings.language = match settings.language {
+                    Language::English => Language::Spanish,
+                    Language::Spanish => Language::English,
+                }
+            }
+            Setting::Difficulty => settings.difficulty = settings.difficulty.next(),
+            Setting::Minimap => settings.minimap = settings.minimap.next(),
+            Setting::MinimapOpacity => {
+                let next = ((settings.minimap_opacity + OPACITY_STEP) / OPACITY_STEP).round()
+                    * OPACITY_STEP;
+                settings.minimap_opacity = if next > 1. + OPACITY_STEP / 2. {
+                    OPACITY_STEP
+                } else {
+                    next
+                };
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct SettingsMenu;
+
+#[derive(Component)]
+struct SettingText(Setting);
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuScreen::Settings), setup_settings_menu)
+            .add_systems(
+                Update,
+                update_setting_text.run_if(in_state(MenuScreen::Settings)),
+            )
+            .add_systems(OnExit(MenuScreen::Settings), cleanup_screen::<SettingsMenu>);
+    }
+}
+
+fn setup_settings_menu(mut commands: Commands, settings: Res<Settings>) {
+    let button_style = Style {
+        width: Val::Px(400.0),
+        height: Val::Px(36.0),
+        margin: UiRect::all(Val::Px(4.0)),
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 22.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    let button_colors = ButtonColors::default();
+
+    commands
+        .spawn((screen_root(), SettingsMenu))
+        .with_children(|parent| {
+            // Two columns, so every setting fits on the smallest window
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        max_width: Val::Px(820.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for setting in SETTINGS {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: button_colors.normal.into(),
+                                    ..Default::default()
+                                },
+                                button_colors,
+                                MenuButtonAction::ChangeSetting(setting),
+                                Focusable,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    TextBundle::from_section(
+                                        format!(
+                                            "{}: {}",
+                                            settings.language.tr(setting.label()),
+                                            setting.value(&settings)
+                                        ),
+                                        button_text_style.clone(),
+                                    ),
+                                    SettingText(setting),
+                                ));
+                            });
+                    }
+                });
+            spawn_button(
+                parent,
+                "Back",
+                MenuButtonAction::Back,
+                Style {
+                    margin: UiRect::top(Val::Px(20.0)),
+                    ..button_style
+                },
+                button_text_style,
+                settings.language,
+            );
+        });
+}
+
+fn update_setting_text(settings: Res<Settings>, mut text_query: Query<(&mut Text, &SettingText)>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut text, SettingText(setting)) in &mut text_query {
+        text.sections[0].value = format!(
+            "{}: {}",
+            settings.language.tr(setting.label()),
+            setting.value(&settings)
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+
+use crate::settings::{Language, Settings};
+use crate::upgrades::{Upgrade, Upgrades};
+
+use super::navigation::{Focusable, MenuActivated};
+use super::{
+    cleanup_screen, screen_root, spawn_button, ButtonColors, MenuButtonAction, MenuScreen,
+    TranslatedText, TEXT_COLOR,
+};
+
+const GOLD_COLOR: Color = Color::GOLD;
+const DESCRIPTION_COLOR: Color = Color::rgb(0.7, 0.7, 0.7);
+
+pub struct ShopMenuPlugin;
+
+#[derive(Component)]
+struct ShopMenu;
+
+#[derive(Component)]
+struct GoldText;
+
+/// Buys the next level of an upgrade
+#[derive(Component)]
+struct ShopButton(Upgrade);
+
+/// The level and price shown on a [`ShopButton`]
+#[derive(Component)]
+struct UpgradeText(Upgrade);
+
+impl Plugin for ShopMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuScreen::Shop), setup_shop_menu)
+            .add_systems(
+                Update,
+                (buy_upgrade, update_shop)
+                    .chain()
+                    .run_if(in_state(MenuScreen::Shop)),
+            )
+            .add_systems(OnExit(MenuScreen::Shop), cleanup_screen::<ShopMenu>);
+    }
+}
+
+fn gold_label(upgrades: &Upgrades, language: Language) -> String {
+    format!("{}: {}", language.tr("Gold"), upgrades.gold)
+}
+
+fn upgrade_label(upgrade: Upgrade, upgrades: &Upgrades, language: Language) -> String {
+    let level = upgrades.level(upgrade);
+    let price = match upgrades.next_cost(upgrade) {
+        Some(cost) => cost.to_string(),
+        None => language.tr("Max").to_string(),
+    };
+    format!(
+        "{}  {level}/{}  -  {price}",
+        language.tr(upgrade.name()),
+        upgrade.max_level()
+    )
+}
+
+fn setup_shop_menu(mut commands: Commands, settings: Res<Settings>, upgrades: Res<Upgrades>) {
+    let language = settings.language;
+    commands
+        .spawn((screen_root(), ShopMenu))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    language.tr("Shop"),
+                    TextStyle {
+                        font_size: 40.0,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                ),
+                TranslatedText("Shop"),
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    gold_label(&upgrades, language),
+                    TextStyle {
+                        font_size: 24.0,
+                        color: GOLD_COLOR,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                }),
+                GoldText,
+            ));
+            for upgrade in Upgrade::ALL {
+                let button_colors = ButtonColors::default();
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(420.0),
+                                margin: UiRect::all(Val::Px(5.0)),
+                                padding: UiRect::all(Val::Px(6.0)),
+                                flex_direction: FlexDirection::Column,
+                                ..default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..default()
+                        },
+                        button_colors,
+                        ShopButton(upgrade),
+                        Focusable,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section(
+                                upgrade_label(upgrade, &upgrades, language),
+                                TextStyle {
+                                    font_size: 22.0,
+                                    color: TEXT_COLOR,
+                                    ..default()
+                                },
+                            ),
+                            UpgradeText(upgrade),
+                        ));
+                        parent.spawn((
+                            TextBundle::from_section(
+                                language.tr(upgrade.description()),
+                                TextStyle {
+                                    font_size: 16.0,
+                                    color: DESCRIPTION_COLOR,
+                                    ..default()
+                                },
+                            ),
+                            TranslatedText(upgrade.description()),
+                        ));
+                    });
+            }
+            spawn_button(
+                parent,
+                "Back",
+                MenuButtonAction::Back,
+                Style {
+                    width: Val::Px(250.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                TextStyle {
+                    font_size: 30.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+                language,
+            );
+        });
+}
+
+fn buy_upgrade(
+    mut upgrades: ResMut<Upgrades>,
+    button_query: Query<&ShopButton>,
+    mut activated_events: EventReader<MenuActivated>,
+) {
+    for MenuActivated(entity) in activated_events.read() {
+        if let Ok(ShopButton(upgrade)) = button_query.get(*entity) {
+            upgrades.buy(*upgrade);
+        }
+    }
+}
+
+fn update_shop(
+    settings: Res<Settings>,
+    upgrades: Res<Upgrades>,
+    mut gold_query: Query<&mut Text, (With<GoldText>, Without<UpgradeText>)>,
+    mut upgrade_query: Query<(&mut Text, &UpgradeText)>,
+) {
+    if !upgrades.is_changed() && !settings.is_changed() {
+        return;
+    }
+    let language = settings.language;
+    for mut text in &mut gold_query {
+        text.sections[0].value = gold_label(&upgrades, language);
+    }
+    for (mut text, UpgradeText(upgrade)) in &mut upgrade_query {
+        text.sections[0].value = upgrade_label(*upgrade, &upgrades, language);
+    }
+}
@@ -0,0 +1,172 @@
+//! A small map of the arena in the corner of the screen, with a dot for the player, every enemy
+//! and every pickup. Its size and opacity come from the settings, where it can also be turned off.
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::enemy::{Enemy, EnemyKind};
+use crate::pickup::{ExperienceOrb, Pickup};
+use crate::player::Player;
+use crate::settings::Settings;
+use crate::GameState;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.1, 0.12, 0.1);
+const BORDER_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+
+pub struct MinimapPlugin;
+
+#[derive(Component)]
+struct Minimap;
+
+/// One dot on the minimap, reused from frame to frame
+#[derive(Component)]
+struct MinimapDot;
+
+/// What a dot stands for
+#[derive(Clone, Copy)]
+enum Blip {
+    Player,
+    Enemy,
+    /// Enemies stronger than the player
+    Hunter,
+    Pickup,
+}
+
+impl Blip {
+    fn color(&self) -> Color {
+        match self {
+            Blip::Player => Color::WHITE,
+            Blip::Enemy => Color::rgb(0.9, 0.2, 0.2),
+            Blip::Hunter => Color::rgb(1., 0.6, 0.1),
+            Blip::Pickup => Color::rgb(0.3, 0.9, 1.),
+        }
+    }
+
+    fn size(&self) -> f32 {
+        match self {
+            Blip::Player | Blip::Hunter => 6.,
+            Blip::Enemy => 4.,
+            Blip::Pickup => 3.,
+        }
+    }
+}
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_minimap)
+            .add_systems(Update, update_minimap.run_if(in_state(GameState::Playing)))
+            .add_systems(OnExit(GameState::Playing), despawn_minimap);
+    }
+}
+
+fn spawn_minimap(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let (Some(width), Ok(window)) = (settings.minimap.width(), window_query.get_single()) else {
+        return;
+    };
+    // The arena is the window, so the map has its shape
+    let height = width * window.height() / window.width();
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(20.0),
+                bottom: Val::Px(20.0),
+                width: Val::Px(width),
+                height: Val::Px(height),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            background_color: BACKGROUND_COLOR.with_a(settings.minimap_opacity).into(),
+            border_color: BORDER_COLOR.with_a(settings.minimap_opacity).into(),
+            ..default()
+        },
+        Minimap,
+    ));
+}
+
+/// Moves a dot to every blip, spawning more dots when there are not enough and hiding the rest
+#[allow(clippy::too_many_arguments)]
+fn update_minimap(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    minimap_query: Query<(Entity, Option<&Children>), With<Minimap>>,
+    mut dot_query: Query<(&mut Style, &mut BackgroundColor, &mut Visibility), With<MinimapDot>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    player_query: Query<&Transform, With<Player>>,
+    enemy_query: Query<(&Transform, &Enemy)>,
+    pickup_query: Query<&Transform, Or<(With<ExperienceOrb>, With<Pickup>)>>,
+) {
+    let (Ok((minimap, children)), Ok(window)) =
+        (minimap_query.get_single(), window_query.get_single())
+    else {
+        return;
+    };
+    let blips = pickup_query
+        .iter()
+        .map(|transform| (transform, Blip::Pickup))
+        .chain(enemy_query.iter().map(|(transform, enemy)| {
+            let blip = match enemy.kind {
+                EnemyKind::Ninja => Blip::Enemy,
+                EnemyKind::PackHunter => Blip::Hunter,
+            };
+            (transform, blip)
+        }))
+        // Drawn last, so it stays on top
+        .chain(
+            player_query
+                .iter()
+                .map(|transform| (transform, Blip::Player)),
+        );
+
+    let size = Vec2::new(window.width(), window.height());
+    let mut dots = children.into_iter().flatten().copied();
+    for (transform, blip) in blips {
+        // From 0 to 1 across the arena, with y going down like the interface
+        let position = transform.translation.truncate() / size + Vec2::splat(0.5);
+        let position = Vec2::new(position.x, 1. - position.y).clamp(Vec2::ZERO, Vec2::ONE);
+        let style = Style {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(position.x * 100.),
+            top: Val::Percent(position.y * 100.),
+            width: Val::Px(blip.size()),
+            height: Val::Px(blip.size()),
+            margin: UiRect::all(Val::Px(-blip.size() / 2.)),
+            ..default()
+        };
+        let color = blip.color().with_a(settings.minimap_opacity);
+        match dots.next().and_then(|dot| dot_query.get_mut(dot).ok()) {
+            Some((mut dot_style, mut dot_color, mut visibility)) => {
+                *dot_style = style;
+                *dot_color = color.into();
+                *visibility = Visibility::Inherited;
+            }
+            None => {
+                commands.entity(minimap).with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style,
+                            background_color: color.into(),
+                            ..default()
+                        },
+                        MinimapDot,
+                    ));
+                });
+            }
+        }
+    }
+    for dot in dots {
+        if let Ok((_, _, mut visibility)) = dot_query.get_mut(dot) {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+fn despawn_minimap(mut commands: Commands, minimap_query: Query<Entity, With<Minimap>>) {
+    for entity in &minimap_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,330 @@
+//! Shares finished runs with a leaderboard server, only built with the `online` feature.
+//!
+//! The protocol is JSON over HTTP:
+//! * `POST /scores` with a [`Submission`] records a run
+//! * `GET /scores?difficulty=Normal&arena=Desert&limit=10` returns the best entries of a table,
+//!   best first
+//!
+//! `src/bin/leaderboard_server/main.rs` and `server.rs` implement it for local development.
+
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::menu::leaderboard::LeaderboardEntry;
+use crate::run::{Arena, Difficulty, RunFinished};
+use crate::storage;
+
+const DEFAULT_SERVER_URL: &str = "http://127.0.0.1:8080";
+const QUEUE_FILE: &str = "online_queue.toml";
+/// How many entries are fetched for the global leaderboard
+const TOP_COUNT: usize = 10;
+// Failed submissions are retried after this many seconds, doubling up to the maximum
+const MIN_RETRY_DELAY: f32 = 2.;
+const MAX_RETRY_DELAY: f32 = 60.;
+
+pub struct OnlinePlugin;
+
+/// A run as it is sent to the server
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Submission {
+    pub entry: LeaderboardEntry,
+    /// There are no replays yet, so this is a checksum of what a replay would have to reproduce
+    pub replay_hash: String,
+}
+
+impl Submission {
+    fn new(entry: LeaderboardEntry) -> Self {
+        let summary = format!(
+            "{}:{}:{}:{}:{:.3}:{}",
+            entry.seed, entry.score, entry.level, entry.kills, entry.survival_time, entry.version
+        );
+        // FNV-1a, so the hash stays the same across platforms and compiler versions
+        let hash = summary
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+            });
+        Submission {
+            entry,
+            replay_hash: format!("{hash:016x}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ServerStatus {
+    /// Nothing was asked of the server yet
+    #[default]
+    Idle,
+    Loading,
+    Online,
+    Unreachable,
+}
+
+/// The global leaderboard of the table that was asked for last
+#[derive(Resource, Default)]
+pub struct OnlineLeaderboard {
+    pub status: ServerStatus,
+    pub top: Vec<LeaderboardEntry>,
+    /// Runs that haven't reached the server yet
+    pub queued: usize,
+    requested: Option<(Difficulty, Arena)>,
+}
+
+/// Asks the server for the best runs of a table, the answer ends up in [`OnlineLeaderboard`]
+#[derive(Event)]
+pub struct FetchTopRuns {
+    pub difficulty: Difficulty,
+    pub arena: Arena,
+}
+
+/// Runs waiting to be sent, saved so they survive being offline for a while
+#[derive(Resource, Serialize, Deserialize, Default)]
+struct SubmissionQueue {
+    submissions: Vec<Submission>,
+    #[serde(skip)]
+    sending: bool,
+    /// Counts down after a failed submission
+    #[serde(skip)]
+    retry: Option<Timer>,
+    #[serde(skip)]
+    retry_delay: f32,
+}
+
+impl SubmissionQueue {
+    fn load() -> Self {
+        let Some(contents) = storage::load(QUEUE_FILE) else {
+            return SubmissionQueue::default();
+        };
+        toml::from_str(&contents).unwrap_or_else(|error| {
+            warn!("Ignoring invalid {QUEUE_FILE}: {error}");
+            SubmissionQueue::default()
+        })
+    }
+
+    fn save(&self) {
+        match toml::to_string(self) {
+            Ok(contents) => storage::save(QUEUE_FILE, &contents),
+            Err(error) => warn!("Failed to save the online queue: {error}"),
+        }
+    }
+}
+
+enum Reply {
+    Submitted(Result<(), String>),
+    TopRuns {
+        table: (Difficulty, Arena),
+        result: Result<Vec<LeaderboardEntry>, String>,
+    },
+}
+
+/// Sends requests from background threads, their replies are picked up once a frame
+#[derive(Resource)]
+struct OnlineClient {
+    url: String,
+    replies: Arc<Mutex<Vec<Reply>>>,
+}
+
+impl OnlineClient {
+    fn new() -> Self {
+        OnlineClient {
+            url: std::env::var("LEADERBOARD_URL").unwrap_or_else(|_| DEFAULT_SERVER_URL.into()),
+            replies: default(),
+        }
+    }
+
+    fn reply(&self) -> impl Fn(Reply) + Send + 'static {
+        let replies = self.replies.clone();
+        move |reply| replies.lock().unwrap().push(reply)
+    }
+
+    fn submit(&self, submission: &Submission) {
+        let reply = self.reply();
+        let request = match ehttp::Request::json(format!("{}/scores", self.url), submission) {
+            Ok(request) => request,
+            Err(error) => return reply(Reply::Submitted(Err(error.to_string()))),
+        };
+        ehttp::fetch(request, move |result| {
+            let result = result.and_then(|response| {
+                if response.ok {
+                    Ok(())
+                } else {
+                    Err(format!("{} {}", response.status, response.status_text))
+                }
+            });
+            reply(Reply::Submitted(result));
+        });
+    }
+
+    fn fetch_top(&self, difficulty: Difficulty, arena: Arena) {
+        let reply = self.reply();
+        let request = ehttp::Request::get(format!(
+            "{}/scores?difficulty={difficulty:?}&arena={arena:?}&limit={TOP_COUNT}",
+            self.url
+        ));
+        ehttp::fetch(request, move |result| {
+            let result = result.and_then(|response| {
+                if !response.ok {
+                    return Err(format!("{} {}", response.status, response.status_text));
+                }
+                response
+                    .json::<Vec<serde_json::Value>>()
+                    .map(known_entries)
+                    .map_err(|error| error.to_string())
+            });
+            reply(Reply::TopRuns {
+                table: (difficulty, arena),
+                result,
+            });
+        });
+    }
+}
+
+/// Skips the entries this version can't read, so one odd entry doesn't hide the whole table
+fn known_entries(entries: Vec<serde_json::Value>) -> Vec<LeaderboardEntry> {
+    entries
+        .into_iter()
+        .filter_map(|entry| serde_json::from_value(entry).ok())
+        .collect()
+}
+
+impl Plugin for OnlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(OnlineClient::new())
+            .insert_resource(SubmissionQueue::load())
+            .init_resource::<OnlineLeaderboard>()
+            .add_event::<FetchTopRuns>()
+            .add_systems(
+                Update,
+                (
+                    queue_finished_runs,
+                    send_queued_runs,
+                    fetch_top_runs,
+                    receive_replies,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn queue_finished_runs(
+    mut queue: ResMut<SubmissionQueue>,
+    mut finished_events: EventReader<RunFinished>,
+) {
+    for RunFinished(report) in finished_events.read() {
+        queue
+            .submissions
+            .push(Submission::new(report.entry.clone()));
+        queue.save();
+    }
+}
+
+fn send_queued_runs(
+    time: Res<Time>,
+    client: Res<OnlineClient>,
+    mut queue: ResMut<SubmissionQueue>,
+) {
+    if queue.sending || queue.submissions.is_empty() {
+        return;
+    }
+    if let Some(retry) = queue.retry.as_mut() {
+        if !retry.tick(time.delta()).finished() {
+            return;
+        }
+    }
+    queue.retry = None;
+    queue.sending = true;
+    client.submit(&queue.submissions[0]);
+}
+
+fn fetch_top_runs(
+    client: Res<OnlineClient>,
+    mut online: ResMut<OnlineLeaderboard>,
+    mut fetch_events: EventReader<FetchTopRuns>,
+) {
+    if let Some(fetch) = fetch_events.read().last() {
+        online.status = ServerStatus::Loading;
+        online.top.clear();
+        online.requested = Some((fetch.difficulty, fetch.arena));
+        client.fetch_top(fetch.difficulty, fetch.arena);
+    }
+}
+
+fn receive_replies(
+    client: Res<OnlineClient>,
+    mut queue: ResMut<SubmissionQueue>,
+    mut online: ResMut<OnlineLeaderboard>,
+) {
+    let replies: Vec<Reply> = client.replies.lock().unwrap().drain(..).collect();
+    for reply in replies {
+        match reply {
+            Reply::Submitted(Ok(())) => {
+                queue.submissions.remove(0);
+                queue.sending = false;
+                queue.retry_delay = 0.;
+                queue.save();
+            }
+            Reply::Submitted(Err(error)) => {
+                warn!("Failed to submit a run, it will be retried: {error}");
+                queue.sending = false;
+                queue.retry_delay =
+                    (queue.retry_delay * 2.).clamp(MIN_RETRY_DELAY, MAX_RETRY_DELAY);
+                queue.retry = Some(Timer::from_seconds(queue.retry_delay, TimerMode::Once));
+                online.status = ServerStatus::Unreachable;
+            }
+            Reply::TopRuns { table, result } => {
+                if online.requested != Some(table) {
+                    continue;
+                }
+                match result {
+                    Ok(top) => {
+                        online.top = top;
+                        online.status = ServerStatus::Online;
+                        // The server is back, no need to wait before sending the rest
+                        queue.retry = None;
+                    }
+                    Err(error) => {
+                        warn!("Failed to fetch the global leaderboard: {error}");
+                        online.top.clear();
+                        online.status = ServerStatus::Unreachable;
+                    }
+                }
+            }
+        }
+    }
+    if online.queued != queue.submissions.len() {
+        online.queued = queue.submissions.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_entries_it_cannot_read() {
+        let entry = LeaderboardEntry {
+            name: "Good".to_string(),
+            score: 10,
+            level: 3,
+            survival_time: 42.5,
+            kills: 12,
+            date: 1_700_000_000,
+            seed: 7,
+            version: "0.2.0".to_string(),
+            difficulty: Difficulty::Normal,
+            arena: Arena::Desert,
+            character: String::new(),
+        };
+        let mut unknown_difficulty = serde_json::to_value(&entry).unwrap();
+        unknown_difficulty["difficulty"] = "Foo".into();
+        let entries = vec![
+            serde_json::json!({ "name": "Missing", "score": 30 }),
+            unknown_difficulty,
+            serde_json::to_value(&entry).unwrap(),
+        ];
+        assert_eq!(known_entries(entries), vec![entry]);
+    }
+}
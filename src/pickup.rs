@@ -0,0 +1,422 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::Deserialize;
+
+use crate::audio::{PlaySfx, Sfx};
+use crate::data::{DataAsset, DataAssetApp};
+use crate::enemy::{Enemy, EnemyKilled, EnemyKind};
+use crate::feedback::ScreenShake;
+use crate::item::{Arsenal, EvolutionList, PassiveItems, Weapon, WeaponEvolved};
+use crate::loading::{DataAssets, TextureAssets};
+use crate::player::{LevelUp, Player};
+use crate::run::Run;
+use crate::stats::{Stat, Stats};
+use crate::status::{StatusEffect, StatusEffects, StatusKind};
+use crate::upgrades::{Upgrade, UpgradeLevels};
+use crate::GameState;
+
+const ORB_SIZE: f32 = 8.;
+const ORB_COLOR: Color = Color::rgb(0.3, 0.9, 1.);
+// How fast orbs fly once the player is in magnet range
+const ORB_SPEED: f32 = 350.;
+// Orbs this close are picked up
+const COLLECT_DISTANCE: f32 = 20.;
+// Seconds a dropped pickup stays around
+const PICKUP_LIFETIME: f32 = 12.;
+// Pickups blink for this many seconds before they disappear
+const PICKUP_WARNING: f32 = 3.;
+// Seconds the temporary power-ups last
+const POWER_UP_DURATION: f32 = 8.;
+const HEALTH_PICKUP_AMOUNT: f32 = 1.;
+
+pub struct PickupPlugin;
+
+/// Dropped by enemies, gives its experience to the player when picked up
+#[derive(Component)]
+pub struct ExperienceOrb(pub i32);
+
+/// Flies to the player from anywhere in the arena
+#[derive(Component)]
+struct Magnetized;
+
+/// Sent with the experience the player actually gained from an orb
+#[derive(Event)]
+pub struct ExperienceCollected(pub i32);
+
+/// Everything besides experience that enemies can drop
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PickupKind {
+    Health,
+    /// More damage for a while
+    Might,
+    /// More speed for a while
+    Haste,
+    /// Can't be hit for a while
+    Invulnerability,
+    /// Defeats every enemy in the arena
+    Bomb,
+    /// Pulls in every experience orb
+    Magnet,
+    /// Evolves a weapon that is ready for it, otherwise one more level of a random upgrade for
+    /// the rest of the run
+    Chest,
+}
+
+impl PickupKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PickupKind::Health => "Health",
+            PickupKind::Might => "Might",
+            PickupKind::Haste => "Haste",
+            PickupKind::Invulnerability => "Invulnerability",
+            PickupKind::Bomb => "Bomb",
+            PickupKind::Magnet => "Magnet",
+            PickupKind::Chest => "Chest",
+        }
+    }
+
+    /// Its picture in the icon sheet
+    fn icon(&self) -> usize {
+        match self {
+            PickupKind::Health => 6,
+            PickupKind::Might => 152,
+            PickupKind::Haste => 8,
+            PickupKind::Invulnerability => 97,
+            PickupKind::Bomb => 9,
+            PickupKind::Magnet => 289,
+            PickupKind::Chest => 187,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Drop {
+    pub pickup: PickupKind,
+    /// Between 0 and 1
+    pub chance: f32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DropTable {
+    pub enemy: EnemyKind,
+    pub drops: Vec<Drop>,
+}
+
+/// What every kind of enemy can drop, from `assets/data/game.drops.toml`
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct DropTables {
+    #[serde(rename = "table")]
+    pub tables: Vec<DropTable>,
+}
+
+impl DropTables {
+    /// Rolls once against the table of the enemy kind
+    fn roll(&self, kind: EnemyKind, rng: &mut impl Rng) -> Option<PickupKind> {
+        let table = self.tables.iter().find(|table| table.enemy == kind)?;
+        let mut roll = rng.gen::<f32>();
+        for drop in &table.drops {
+            if roll < drop.chance {
+                return Some(drop.pickup);
+            }
+            roll -= drop.chance;
+        }
+        None
+    }
+}
+
+impl DataAsset for DropTables {
+    const EXTENSION: &'static str = "drops.toml";
+
+    fn validate(&self) -> Result<(), String> {
+        let mut kinds = HashSet::new();
+        for table in &self.tables {
+            if !kinds.insert(table.enemy) {
+                return Err(format!("there are two drop tables for {:?}", table.enemy));
+            }
+            if table
+                .drops
+                .iter()
+                .any(|drop| drop.chance <= 0. || drop.chance > 1.)
+            {
+                return Err(format!(
+                    "the chances for {:?} have to be above 0 and at most 1",
+                    table.enemy
+                ));
+            }
+            let total: f32 = table.drops.iter().map(|drop| drop.chance).sum();
+            if total > 1. {
+                return Err(format!(
+                    "the chances for {:?} add up to {total}, more than 1",
+                    table.enemy
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A dropped pickup waiting for the player
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    lifetime: Timer,
+}
+
+/// What a chest gave
+#[derive(Clone, Copy, Debug)]
+pub enum ChestReward {
+    Upgrade(Upgrade),
+    /// The weapon one of the player's weapons evolved into
+    Evolution(Weapon),
+}
+
+/// Sent when the player picks something up
+#[derive(Event)]
+pub struct PickupCollected {
+    pub kind: PickupKind,
+    pub position: Vec2,
+    pub reward: Option<ChestReward>,
+}
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_data_asset::<DropTables>()
+            .add_event::<ExperienceCollected>()
+            .add_event::<PickupCollected>()
+            .add_systems(
+                Update,
+                (
+                    drop_experience_orbs,
+                    collect_experience_orbs,
+                    drop_pickups,
+                    collect_pickups,
+                    expire_pickups,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn drop_experience_orbs(mut commands: Commands, mut killed_events: EventReader<EnemyKilled>) {
+    for killed in killed_events.read() {
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform::from_translation(killed.position.extend(0.5)),
+                sprite: Sprite {
+                    color: ORB_COLOR,
+                    custom_size: Some(Vec2::splat(ORB_SIZE)),
+                    ..default()
+                },
+                ..default()
+            },
+            ExperienceOrb(killed.experience),
+        ));
+    }
+}
+
+fn collect_experience_orbs(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<(&Transform, &mut Player, &mut StatusEffects, &Stats)>,
+    mut orb_query: Query<
+        (Entity, &mut Transform, &ExperienceOrb, Has<Magnetized>),
+        Without<Player>,
+    >,
+    mut collected_events: EventWriter<ExperienceCollected>,
+    mut level_up_events: EventWriter<LevelUp>,
+) {
+    let Ok((player_transform, mut player, mut statuses, stats)) = player_query.get_single_mut()
+    else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+    let magnet_range = stats.get(Stat::MagnetRange);
+    for (entity, mut transform, orb, magnetized) in &mut orb_query {
+        let offset = player_position - transform.translation.truncate();
+        let distance = offset.length();
+        if distance > magnet_range && !magnetized {
+            continue;
+        }
+        if distance > COLLECT_DISTANCE {
+            let step = (ORB_SPEED * time.delta_seconds()).min(distance);
+            transform.translation += (offset / distance * step).extend(0.);
+            continue;
+        }
+        commands.entity(entity).despawn_recursive();
+        let (gained, levelled_up) =
+            player.collect_experience(orb.0, stats.get(Stat::ExperienceGain));
+        collected_events.send(ExperienceCollected(gained));
+        if levelled_up {
+            level_up_events.send_default();
+            // Levelling up gives a short burst of speed and protection
+            statuses.apply(StatusEffect::new(StatusKind::Haste, 0.5, 5.));
+            statuses.apply(StatusEffect::new(StatusKind::Shield, 0., 5.));
+        }
+    }
+}
+
+fn drop_pickups(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    data: Res<DataAssets>,
+    tables: Res<Assets<DropTables>>,
+    mut run: ResMut<Run>,
+    mut killed_events: EventReader<EnemyKilled>,
+) {
+    let Some(tables) = tables.get(&data.drops) else {
+        return;
+    };
+    for killed in killed_events.read() {
+        let Some(kind) = tables.roll(killed.kind, &mut run.rng) else {
+            continue;
+        };
+        commands.spawn((
+            SpriteSheetBundle {
+                // Next to the experience orb instead of on top of it
+                transform: Transform::from_translation(
+                    (killed.position + Vec2::X * 12.).extend(0.6),
+                )
+                .with_scale(Vec3::splat(0.75)),
+                texture: textures.icons.clone(),
+                atlas: TextureAtlas {
+                    layout: textures.icons_layout.clone(),
+                    index: kind.icon(),
+                },
+                ..default()
+            },
+            Pickup {
+                kind,
+                lifetime: Timer::from_seconds(PICKUP_LIFETIME, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn collect_pickups(
+    mut commands: Commands,
+    mut run: ResMut<Run>,
+    mut shake: ResMut<ScreenShake>,
+    data: Res<DataAssets>,
+    evolutions: Res<Assets<EvolutionList>>,
+    mut player_query: Query<(
+        &Transform,
+        &mut Player,
+        &mut StatusEffects,
+        &mut Stats,
+        &mut Arsenal,
+        &PassiveItems,
+        &mut UpgradeLevels,
+    )>,
+    pickup_query: Query<(Entity, &Transform, &Pickup), Without<Player>>,
+    orb_query: Query<Entity, With<ExperienceOrb>>,
+    mut enemy_query: Query<&mut Enemy>,
+    mut collected_events: EventWriter<PickupCollected>,
+    mut evolved_events: EventWriter<WeaponEvolved>,
+    mut sfx_events: EventWriter<PlaySfx>,
+) {
+    let Ok((
+        player_transform,
+        mut player,
+        mut statuses,
+        mut stats,
+        mut arsenal,
+        items,
+        mut upgrade_levels,
+    )) = player_query.get_single_mut()
+    else {
+        return;
+    };
+    if player.health <= 0. {
+        return;
+    }
+    let player_position = player_transform.translation.truncate();
+    for (entity, transform, pickup) in &pickup_query {
+        let position = transform.translation.truncate();
+        if position.distance(player_position) > COLLECT_DISTANCE {
+            continue;
+        }
+        commands.entity(entity).despawn_recursive();
+        let mut reward = None;
+        match pickup.kind {
+            PickupKind::Health => {
+                player.health =
+                    (player.health + HEALTH_PICKUP_AMOUNT).min(stats.get(Stat::MaxHealth));
+            }
+            PickupKind::Might => {
+                statuses.apply(StatusEffect::new(StatusKind::Might, 0.5, POWER_UP_DURATION));
+            }
+            PickupKind::Haste => {
+                statuses.apply(StatusEffect::new(StatusKind::Haste, 0.4, POWER_UP_DURATION));
+            }
+            PickupKind::Invulnerability => {
+                player.invulnerable = player.invulnerable.max(POWER_UP_DURATION);
+            }
+            PickupKind::Bomb => {
+                for mut enemy in &mut enemy_query {
+                    enemy.health = 0.;
+                }
+                shake.add_trauma(1.);
+            }
+            PickupKind::Magnet => {
+                for orb in &orb_query {
+                    commands.entity(orb).insert(Magnetized);
+                }
+            }
+            PickupKind::Chest => {
+                // A weapon that is ready to evolve takes precedence over an upgrade
+                let evolution = evolutions
+                    .get(&data.evolutions)
+                    .and_then(|list| list.evolve(&mut player, &mut arsenal, items));
+                if let Some(evolution) = evolution {
+                    evolved_events.send_default();
+                    reward = Some(ChestReward::Evolution(evolution.into));
+                } else {
+                    let chosen = *Upgrade::ALL
+                        .choose(&mut run.rng)
+                        .expect("There are no upgrades");
+                    chosen.grant(&mut upgrade_levels, &mut player, &mut stats);
+                    reward = Some(ChestReward::Upgrade(chosen));
+                }
+            }
+        }
+        sfx_events.send(PlaySfx(if pickup.kind == PickupKind::Bomb {
+            Sfx::Explosion
+        } else {
+            Sfx::LevelUp
+        }));
+        collected_events.send(PickupCollected {
+            kind: pickup.kind,
+            position,
+            reward,
+        });
+    }
+}
+
+/// Pickups blink for a while before they disappear
+fn expire_pickups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pickup_query: Query<(Entity, &mut Pickup, &mut Visibility)>,
+) {
+    for (entity, mut pickup, mut visibility) in &mut pickup_query {
+        pickup.lifetime.tick(time.delta());
+        if pickup.lifetime.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let remaining = pickup.lifetime.remaining_secs();
+        let shown = remaining > PICKUP_WARNING || (remaining * 8.) as i32 % 2 == 0;
+        let wanted = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+}
@@ -2,11 +2,26 @@ use std::ops::AddAssign;
 
 use crate::{
     actions::Actions,
-    enemy::{Enemy, SpawnTimer},
-    item::Bullet,
+    animation::{ClipName, SpriteAnimation},
+    character::{selected_character, Character, CharacterList, PassiveAbility},
+    dash::Dash,
+    disguise::Disguise,
+    enemy::{Enemy, SpawnTelegraph, SpawnTimer},
+    feedback::ScreenShake,
+    item::{Arsenal, Bullet, PassiveItems, Weapon},
     level::Level,
-    loading::TextureAssets,
-    menu::{leaderboard::PlayerName, Leaderboard, Score},
+    loading::{DataAssets, TextureAssets},
+    menu::{
+        leaderboard::{unix_time, LeaderboardEntry},
+        Leaderboard, Score,
+    },
+    pickup::{ExperienceOrb, Pickup},
+    profile::Profiles,
+    run::{Run, RunFinished, RunReport, RunStats},
+    settings::Settings,
+    stats::{Stat, Stats},
+    status::{StatusEffects, StatusKind},
+    upgrades::Upgrades,
     GameState,
 };
 use bevy::{
@@ -27,14 +42,41 @@ impl AddAssign for Experience {
     }
 }
 
+// Enemies take this much health when they touch the player
+const CONTACT_DAMAGE: f32 = 1.;
+// Armor can't make a hit weaker than this
+const MIN_CONTACT_DAMAGE: f32 = 0.2;
+// Seconds after a hit during which the player can't be hit again
+const INVULNERABILITY_DURATION: f32 = 1.;
+
+/// Where the player can go, the window minus half the size of the player
+pub fn player_bounds(window: &Window) -> Rect {
+    let half_player_size = 32.;
+    Rect::new(
+        -(window.width() / 2.0) + half_player_size,
+        -(window.height() / 2.0) + half_player_size,
+        window.width() / 2.0 - half_player_size,
+        window.height() / 2.0 - half_player_size,
+    )
+}
+
 #[derive(Component)]
 pub struct Player {
     pub direction: Vec2,
     pub level: Level,
     pub exp: Experience,
+    pub health: f32,
+    /// Thrown with the attack key
+    pub starting_weapon: Weapon,
+    /// How many of the starting weapon are thrown at once before levelling adds more
+    pub weapon_level: i32,
+    /// Seconds left during which hits are ignored
+    pub invulnerable: f32,
+    /// Experience gain leaves fractions that are added to the next orb
+    experience_carry: f32,
 }
 impl Player {
-    pub fn default() -> Self {
+    pub fn new(character: &Character) -> Self {
         Player {
             direction: Vec2::new(1., 0.).normalize(),
             level: Level {
@@ -42,6 +84,11 @@ impl Player {
                 exp_max: 10,
             },
             exp: Experience(0),
+            health: character.stats.max_health,
+            starting_weapon: character.starting_weapon,
+            weapon_level: 1,
+            invulnerable: 0.,
+            experience_carry: 0.,
         }
     }
 
@@ -52,18 +99,54 @@ impl Player {
     //         exp_max: 10,
     //     }
     // }
+    /// Only the starting weapon gains levels, every 5 levels of the player and with upgrades
+    pub fn weapon_level_of(&self, weapon: Weapon) -> i32 {
+        if weapon == self.starting_weapon {
+            self.level.value / 5 + self.weapon_level
+        } else {
+            1
+        }
+    }
+
+    /// How many of a weapon are thrown at once, one for every level of the weapon
+    pub fn projectiles(&self, weapon: Weapon, stats: &Stats) -> i32 {
+        self.weapon_level_of(weapon) + stats.get(Stat::Projectiles) as i32
+    }
+
     pub fn level_up(&mut self) {
         self.level.value += 1;
         self.level.exp_max += 5;
     }
-    pub fn add_experience(&mut self, experience: Experience) {
+    /// Adds experience scaled by the experience gain, returns how much was added and whether the
+    /// player levelled up
+    pub fn collect_experience(&mut self, experience: i32, gain: f32) -> (i32, bool) {
+        self.experience_carry += experience as f32 * gain;
+        let gained = self.experience_carry.floor();
+        self.experience_carry -= gained;
+        let gained = gained as i32;
+        (gained, self.add_experience(Experience(gained)))
+    }
+
+    /// Returns whether the player levelled up
+    pub fn add_experience(&mut self, experience: Experience) -> bool {
         self.exp += experience;
         if self.exp.0 >= self.level.exp_max {
             self.level_up();
             self.exp = Experience(0);
+            return true;
         }
+        false
     }
 }
+#[derive(Event, Default)]
+pub struct LevelUp;
+
+/// Sent when something gets through to the player
+#[derive(Event)]
+pub struct PlayerDamaged {
+    pub amount: f32,
+}
+
 #[derive(Event, Default)]
 pub struct Death {
     pub message: String,
@@ -74,60 +157,134 @@ impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Playing), spawn_player)
             .add_event::<Death>()
+            .add_event::<PlayerDamaged>()
+            .add_event::<LevelUp>()
             .init_resource::<Experience>()
-            .add_systems(Update, move_player.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                Update,
+                (
+                    move_player,
+                    regenerate,
+                    touch_enemies,
+                    blink_while_invulnerable,
+                    animate_player,
+                    finish_dying,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
             .add_systems(OnExit(GameState::Playing), finish_level);
     }
 }
 
-fn spawn_player(mut commands: Commands, textures: Res<TextureAssets>) {
-    commands
-        .spawn(SpriteBundle {
-            transform: Transform::from_translation(Vec3::new(0., 200., 1.))
-                .with_scale(Vec3::new(2., 2., 1.)),
-            texture: textures.cactus.clone(),
-            ..Default::default()
-        })
-        .insert(Player::default());
+fn spawn_player(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    settings: Res<Settings>,
+    data: Res<DataAssets>,
+    characters: Res<Assets<CharacterList>>,
+    upgrades: Res<Upgrades>,
+) {
+    // Loading only finishes once the characters are loaded and valid, see `crate::data`
+    let character = selected_character(&settings, &data, &characters)
+        .expect("The characters are loaded before any run starts");
+    let mut player = Player::new(character);
+    let mut stats = Stats::for_character(character);
+    let upgrade_levels = upgrades.apply(&mut player, &mut stats);
+    let mut entity = commands.spawn(character.sprite_sheet.sprite(
+        &textures,
+        Transform::from_translation(Vec3::new(0., 200., 1.)).with_scale(Vec3::new(2., 2., 1.)),
+    ));
+    entity
+        .insert(player)
+        .insert(stats)
+        .insert(upgrade_levels)
+        .insert(PassiveItems::default())
+        .insert(StatusEffects::default())
+        .insert(Disguise::default())
+        .insert(Dash::new(character.dash))
+        .insert(Arsenal::new(character.starting_weapon));
+    if let Some(passive) = character.passive {
+        entity.insert(PassiveAbility::new(passive));
+    }
 }
 
-#[allow(clippy::too_many_arguments)]
 fn move_player(
-    mut next_state: ResMut<NextState<GameState>>,
     time: Res<Time>,
     actions: Res<Actions>,
-    mut player_query: Query<(&mut Transform, &mut Player, &mut Handle<Image>), With<Player>>,
-    textures: Res<TextureAssets>,
+    mut player_query: Query<(&mut Transform, &mut Player, &Stats, &Dash)>,
     window_query: Query<&Window, With<PrimaryWindow>>,
-    enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
-    mut collision_event: EventWriter<Death>,
 ) {
-    if actions.player_movement.is_none() {
-        *player_query.single_mut().2 = textures.cactus.clone();
+    let Ok((mut player_transform, mut player, stats, dash)) = player_query.get_single_mut() else {
+        return;
+    };
+    // Standing still, dashing or already defeated
+    if actions.player_movement.is_none() || dash.is_dashing() || player.health <= 0. {
         return;
     }
 
     let window = window_query.get_single().unwrap();
-    let half_player_size = 32.;
-    let speed = 150. + (player_query.single().1.level.value * 10) as f32;
+    let speed = stats.get(Stat::MoveSpeed);
     let movement = Vec3::new(
         actions.player_movement.unwrap().x * speed * time.delta_seconds(),
         actions.player_movement.unwrap().y * speed * time.delta_seconds(),
         0.,
     );
-    let x_min = -(window.width() / 2.0) + half_player_size;
-    let x_max = window.width() / 2.0 - half_player_size;
-    let y_min = -(window.height() / 2.0) + half_player_size;
-    let y_max = window.height() / 2.0 - half_player_size;
-    let (mut player_transform, mut player, mut handle) = player_query.single_mut();
-    *handle = textures.ninja.clone();
-    player.direction = actions.player_movement.unwrap();
+    let bounds = player_bounds(window);
+    if player.direction != actions.player_movement.unwrap() {
+        player.direction = actions.player_movement.unwrap();
+    }
     let new_pos = player_transform.translation + movement;
-    if new_pos.x > x_min && new_pos.x < x_max && new_pos.y > y_min && new_pos.y < y_max {
+    if new_pos.x > bounds.min.x
+        && new_pos.x < bounds.max.x
+        && new_pos.y > bounds.min.y
+        && new_pos.y < bounds.max.y
+    {
         player_transform.translation += movement;
     }
+}
 
-    for enemy_transform in &enemy_query {
+/// Heals the player a little every frame, up to the max health
+fn regenerate(time: Res<Time>, mut player_query: Query<(&mut Player, &Stats)>) {
+    let Ok((mut player, stats)) = player_query.get_single_mut() else {
+        return;
+    };
+    let per_second = stats.get(Stat::Regeneration);
+    if per_second <= 0. || player.health <= 0. {
+        return;
+    }
+    let max_health = stats.get(Stat::MaxHealth);
+    if player.health < max_health {
+        player.health = (player.health + per_second * time.delta_seconds()).min(max_health);
+    }
+}
+
+/// Enemies that run into the player hurt them, unless they are fooled by the disguise
+fn touch_enemies(
+    mut player_query: Query<(
+        &Transform,
+        &mut Player,
+        &mut StatusEffects,
+        &Disguise,
+        &Stats,
+    )>,
+    mut enemy_query: Query<(&Transform, &mut Enemy), Without<Player>>,
+    mut damaged_events: EventWriter<PlayerDamaged>,
+    mut shake: ResMut<ScreenShake>,
+) {
+    let Ok((player_transform, mut player, mut statuses, disguise, stats)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+    if player.health <= 0. {
+        return;
+    }
+    let damage = (CONTACT_DAMAGE - stats.get(Stat::Armor)).max(MIN_CONTACT_DAMAGE);
+    for (enemy_transform, mut enemy) in &mut enemy_query {
+        if disguise.active && !enemy.kind.sees_through_disguise() {
+            continue;
+        }
         let collision = Aabb2d::new(
             player_transform.translation.truncate(),
             player_transform.scale.truncate() * 5.0 / 2.,
@@ -136,47 +293,163 @@ fn move_player(
             enemy_transform.translation.truncate(),
             enemy_transform.scale.truncate() * 10.0 / 2.,
         ));
-        if collision {
-            let msgs = vec![
-                "The ninjas got to you!",
-                "Oh no you got hit again :(",
-                "Did you try running away from the ninjas?",
-                "Press Space to throw your shuriken!",
-                "That was great, but you can do better!",
-                "You need to practice turning into a cactus when you are still.",
-            ];
-
-            collision_event.send(Death {
-                message: msgs
-                    .choose(&mut rand::thread_rng())
-                    .expect("No death message found")
-                    .to_string(),
-            });
-            next_state.set(GameState::Menu);
+        if !collision || enemy.health <= 0. {
+            continue;
+        }
+        if statuses.consume(StatusKind::Shield) {
+            // The shield takes the hit and destroys the enemy instead
+            enemy.health = 0.;
+            shake.add_trauma(0.5);
+        } else if player.invulnerable <= 0. {
+            player.health -= damage;
+            damaged_events.send(PlayerDamaged { amount: damage });
+            if player.health > 0. {
+                player.invulnerable = INVULNERABILITY_DURATION;
+                continue;
+            }
+            // The run ends once the death animation has played
+            break;
         }
     }
 }
+
+/// Picks the clip for what the player is doing, hits and defeat take precedence over moving
+fn animate_player(
+    actions: Res<Actions>,
+    mut damaged_events: EventReader<PlayerDamaged>,
+    mut player_query: Query<(&Player, &Disguise, &mut SpriteAnimation)>,
+) {
+    let hurt = damaged_events.read().count() > 0;
+    let Ok((player, disguise, mut animation)) = player_query.get_single_mut() else {
+        return;
+    };
+    let current = animation.current();
+    let clip = if player.health <= 0. {
+        ClipName::Death
+    } else if hurt || (current == ClipName::Hurt && !animation.is_finished()) {
+        ClipName::Hurt
+    } else if disguise.active {
+        // Turns into a cactus and stays one
+        ClipName::Disguise
+    } else if let Some(movement) = actions.player_movement {
+        ClipName::walk(movement)
+    } else {
+        ClipName::Idle
+    };
+    animation.play(clip);
+}
+
+/// Ends the run once the player was defeated and the death animation is over
+fn finish_dying(
+    mut next_state: ResMut<NextState<GameState>>,
+    player_query: Query<(&Player, &SpriteAnimation)>,
+    mut death_events: EventWriter<Death>,
+) {
+    let Ok((player, animation)) = player_query.get_single() else {
+        return;
+    };
+    if player.health > 0. || animation.current() != ClipName::Death || !animation.is_finished() {
+        return;
+    }
+    let weapon_hint = format!(
+        "Press Space to throw your {}!",
+        player.starting_weapon.name().to_lowercase()
+    );
+    let msgs = [
+        "The ninjas got to you!",
+        "Oh no you got hit again :(",
+        "Did you try running away from the ninjas?",
+        &weapon_hint,
+        "That was great, but you can do better!",
+        "You need to practice turning into a cactus when you are still.",
+        "Pack hunters can tell a cactus from a ninja.",
+    ];
+
+    death_events.send(Death {
+        message: msgs
+            .choose(&mut rand::thread_rng())
+            .expect("No death message found")
+            .to_string(),
+    });
+    next_state.set(GameState::Menu);
+}
+
+/// Counts down the invulnerability after a hit and makes it visible
+fn blink_while_invulnerable(
+    time: Res<Time>,
+    mut player_query: Query<(&mut Player, &mut Visibility)>,
+) {
+    for (mut player, mut visibility) in &mut player_query {
+        // Writing every frame would mark the player as changed and redraw the HUD
+        if player.invulnerable > 0. {
+            player.invulnerable = (player.invulnerable - time.delta_seconds()).max(0.);
+        }
+        let shown = player.invulnerable <= 0. || (player.invulnerable * 10.) as i32 % 2 == 0;
+        let wanted = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn finish_level(
     mut commands: Commands,
-    q_player: Query<Entity, With<Player>>,
-    q_enemy: Query<Entity, With<Enemy>>,
+    q_player: Query<(Entity, &Player)>,
+    q_enemy: Query<
+        Entity,
+        Or<(
+            With<Enemy>,
+            With<SpawnTelegraph>,
+            With<ExperienceOrb>,
+            With<Pickup>,
+        )>,
+    >,
     q_bullets: Query<Entity, With<Bullet>>,
     q_camera: Query<Entity, With<Camera2d>>,
-    player_name: Res<PlayerName>,
+    profiles: Res<Profiles>,
     mut leaderboard: ResMut<Leaderboard>,
     mut score: ResMut<Score>,
     mut timer: ResMut<SpawnTimer>,
+    run: Res<Run>,
+    mut stats: ResMut<RunStats>,
+    mut finished_events: EventWriter<RunFinished>,
 ) {
-    let maybe_name = &*player_name.0;
-    let name = if maybe_name == "" {
-        "Anonymous".to_string()
-    } else {
-        maybe_name.to_string()
+    let name = profiles
+        .current()
+        .map_or_else(|| "Anonymous".to_string(), |profile| profile.name.clone());
+    let level = q_player
+        .iter()
+        .next()
+        .map_or(1, |(_, player)| player.level.value);
+    let entry = LeaderboardEntry {
+        name,
+        score: score.score,
+        level,
+        survival_time: stats.time_alive,
+        kills: stats.kills,
+        date: unix_time(),
+        seed: run.seed,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        difficulty: run.difficulty,
+        arena: run.arena,
+        character: run.character.clone(),
     };
-    leaderboard.add_score(name, score.score);
+    finished_events.send(RunFinished(RunReport {
+        entry: entry.clone(),
+        stats: stats.clone(),
+    }));
+    if let Some(rank) = leaderboard.add_entry(entry) {
+        info!("The run made it on the leaderboard at #{rank}");
+    }
     score.score = 0;
+    *stats = RunStats::default();
     timer.reset();
-    for entity in q_player.iter() {
+    for (entity, _) in q_player.iter() {
         commands.entity(entity).despawn_recursive();
     }
     for entity in q_enemy.iter() {
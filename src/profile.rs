@@ -0,0 +1,256 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::menu::leaderboard::LeaderboardEntry;
+use crate::run::{Arena, Difficulty, RunFinished};
+use crate::storage;
+
+const PROFILES_FILE: &str = "profiles.toml";
+/// In characters, long enough for most names while still fitting in the leaderboard column
+pub const MAX_NAME_LENGTH: usize = 16;
+
+pub struct ProfilePlugin;
+
+/// Why a name can't be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameError {
+    Empty,
+    TooLong,
+    /// Control characters, zero-width characters or text direction marks
+    InvisibleCharacter,
+}
+
+impl NameError {
+    /// English, ready to be translated
+    pub fn message(&self) -> &'static str {
+        match self {
+            NameError::Empty => "Enter a name",
+            NameError::TooLong => "That name is too long",
+            NameError::InvisibleCharacter => "Names can't contain invisible characters",
+        }
+    }
+}
+
+/// Characters that would make names look alike or mess up the text around them
+fn is_invisible(character: char) -> bool {
+    character.is_control()
+        || matches!(
+            character,
+            '\u{00AD}'
+                | '\u{180E}'
+                | '\u{200B}'..='\u{200F}'
+                | '\u{202A}'..='\u{202E}'
+                | '\u{2060}'..='\u{2064}'
+                | '\u{2066}'..='\u{2069}'
+                | '\u{FEFF}'
+        )
+}
+
+/// Trims the surrounding whitespace and checks that what is left can be shown as a name
+pub fn validate_name(name: &str) -> Result<String, NameError> {
+    let name = name.trim();
+    if name.chars().any(is_invisible) {
+        Err(NameError::InvisibleCharacter)
+    } else if name.is_empty() {
+        Err(NameError::Empty)
+    } else if name.chars().count() > MAX_NAME_LENGTH {
+        Err(NameError::TooLong)
+    } else {
+        Ok(name.to_string())
+    }
+}
+
+/// The best score of a profile on one difficulty and arena
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BestScore {
+    pub difficulty: Difficulty,
+    pub arena: Arena,
+    pub score: i32,
+}
+
+/// Someone playing on this device, with the totals of all their runs
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    pub runs: u32,
+    pub kills: u32,
+    /// In seconds
+    pub time_played: f32,
+    pub best_scores: Vec<BestScore>,
+}
+
+impl Profile {
+    fn new(name: String) -> Self {
+        Profile { name, ..default() }
+    }
+
+    pub fn best_score(&self, difficulty: Difficulty, arena: Arena) -> Option<i32> {
+        self.best_scores
+            .iter()
+            .find(|best| best.difficulty == difficulty && best.arena == arena)
+            .map(|best| best.score)
+    }
+
+    fn record(&mut self, entry: &LeaderboardEntry) {
+        self.runs += 1;
+        self.kills += entry.kills;
+        self.time_played += entry.survival_time;
+        match self
+            .best_scores
+            .iter_mut()
+            .find(|best| best.difficulty == entry.difficulty && best.arena == entry.arena)
+        {
+            Some(best) => best.score = best.score.max(entry.score),
+            None => self.best_scores.push(BestScore {
+                difficulty: entry.difficulty,
+                arena: entry.arena,
+                score: entry.score,
+            }),
+        }
+    }
+}
+
+/// Every profile on this device and which one is playing, saved whenever either changes
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Profiles {
+    /// Name of the profile that was picked last
+    current: Option<String>,
+    profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    fn load() -> Self {
+        let Some(contents) = storage::load(PROFILES_FILE) else {
+            return Profiles::default();
+        };
+        let mut profiles: Profiles = toml::from_str(&contents).unwrap_or_else(|error| {
+            warn!("Ignoring invalid {PROFILES_FILE}: {error}");
+            Profiles::default()
+        });
+        // The file may have been edited by hand
+        profiles
+            .profiles
+            .retain(|profile| validate_name(&profile.name).as_ref() == Ok(&profile.name));
+        if profiles.current().is_none() {
+            profiles.current = None;
+        }
+        profiles
+    }
+
+    fn save(&self) {
+        match toml::to_string(self) {
+            Ok(contents) => storage::save(PROFILES_FILE, &contents),
+            Err(error) => warn!("Failed to save profiles: {error}"),
+        }
+    }
+
+    pub fn all(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    pub fn current(&self) -> Option<&Profile> {
+        let current = self.current.as_ref()?;
+        self.profiles
+            .iter()
+            .find(|profile| &profile.name == current)
+    }
+
+    fn current_mut(&mut self) -> Option<&mut Profile> {
+        let current = self.current.as_ref()?;
+        self.profiles
+            .iter_mut()
+            .find(|profile| &profile.name == current)
+    }
+
+    /// Switches to the profile at `index` of [`Profiles::all`]
+    pub fn select(&mut self, index: usize) {
+        if let Some(profile) = self.profiles.get(index) {
+            self.current = Some(profile.name.clone());
+            self.save();
+        }
+    }
+
+    /// Names differing only in case belong to the same profile
+    fn find(&self, name: &str) -> Option<&Profile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name.to_lowercase() == name.to_lowercase())
+    }
+
+    /// Switches to the profile with this name, creating it if there is none yet
+    pub fn select_or_create(&mut self, name: &str) -> Result<(), NameError> {
+        let name = validate_name(name)?;
+        let name = match self.find(&name) {
+            Some(profile) => profile.name.clone(),
+            None => {
+                self.profiles.push(Profile::new(name.clone()));
+                name
+            }
+        };
+        info!("Playing as {name}");
+        self.current = Some(name);
+        self.save();
+        Ok(())
+    }
+}
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Profiles::load())
+            .add_systems(Update, record_finished_runs);
+    }
+}
+
+fn record_finished_runs(
+    mut profiles: ResMut<Profiles>,
+    mut finished_events: EventReader<RunFinished>,
+) {
+    for RunFinished(report) in finished_events.read() {
+        if let Some(profile) = profiles.current_mut() {
+            profile.record(&report.entry);
+            profiles.save();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_need_something_visible() {
+        assert_eq!(validate_name(""), Err(NameError::Empty));
+        assert_eq!(validate_name("   "), Err(NameError::Empty));
+        assert_eq!(
+            validate_name("Ni\u{200B}nja"),
+            Err(NameError::InvisibleCharacter)
+        );
+        assert_eq!(validate_name("Ninja\n"), Ok("Ninja".to_string()));
+    }
+
+    #[test]
+    fn names_are_trimmed_before_counting() {
+        assert_eq!(validate_name("  Ninja  "), Ok("Ninja".to_string()));
+        let longest = "ñ".repeat(MAX_NAME_LENGTH);
+        assert_eq!(validate_name(&format!(" {longest} ")), Ok(longest.clone()));
+        assert_eq!(
+            validate_name(&format!("{longest}a")),
+            Err(NameError::TooLong)
+        );
+    }
+
+    #[test]
+    fn names_differing_in_case_are_the_same_profile() {
+        let profiles = Profiles {
+            current: None,
+            profiles: vec![Profile::new("Ninja".to_string())],
+        };
+        assert_eq!(
+            profiles.find("NINJA").map(|profile| profile.name.as_str()),
+            Some("Ninja")
+        );
+        assert!(profiles.find("Ninja2").is_none());
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::character::{selected_character, CharacterList};
+use crate::enemy::{EnemyDamaged, EnemyKilled, EnemyKind, SpawnTimer};
+use crate::item::{Projectile, Weapon};
+use crate::loading::DataAssets;
+use crate::menu::leaderboard::LeaderboardEntry;
+use crate::pickup::ExperienceCollected;
+use crate::player::{Player, PlayerDamaged};
+use crate::settings::Settings;
+#[cfg(feature = "export")]
+use crate::storage;
+use crate::GameState;
+
+pub struct RunPlugin;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|other| other == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Scales the time between enemy spawns
+    fn spawn_interval(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.5,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 0.6,
+        }
+    }
+}
+
+/// There is only the one arena so far, runs still remember it so every arena gets its own
+/// leaderboard
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Arena {
+    #[default]
+    Desert,
+}
+
+impl Arena {
+    pub const ALL: [Arena; 1] = [Arena::Desert];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Arena::Desert => "Desert",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|other| other == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// How the current run was set up, the seed drives where and how enemies spawn
+#[derive(Resource)]
+pub struct Run {
+    pub seed: u32,
+    pub difficulty: Difficulty,
+    pub arena: Arena,
+    /// The id of the character being played
+    pub character: String,
+    pub rng: StdRng,
+}
+
+impl Default for Run {
+    fn default() -> Self {
+        Run {
+            seed: 0,
+            difficulty: Difficulty::default(),
+            arena: Arena::default(),
+            character: String::new(),
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+}
+
+/// Tallies for the current run, reset once it has been recorded
+#[derive(Resource, Serialize, Default, Debug, Clone)]
+pub struct RunStats {
+    /// In seconds
+    pub time_alive: f32,
+    pub kills: u32,
+    pub kills_by_enemy: BTreeMap<EnemyKind, u32>,
+    /// Kills that no weapon can take the credit for are left out
+    pub kills_by_weapon: BTreeMap<Weapon, u32>,
+    pub damage_dealt: f32,
+    pub damage_taken: f32,
+    /// How far the player moved, in world units
+    pub distance: f32,
+    pub projectiles_fired: u32,
+    pub experience: i32,
+    #[serde(skip)]
+    last_position: Option<Vec2>,
+}
+
+/// A finished run, as it is shared with the rest of the game and exported for balancing
+#[derive(Serialize, Debug, Clone)]
+pub struct RunReport {
+    pub entry: LeaderboardEntry,
+    pub stats: RunStats,
+}
+
+#[cfg(feature = "export")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+#[cfg(feature = "export")]
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Quotes a CSV field when it contains anything that would break the row
+#[cfg(feature = "export")]
+fn csv_field(value: impl ToString) -> String {
+    let value = value.to_string();
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(feature = "export")]
+impl RunReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|error| {
+            warn!("Failed to export the run as JSON: {error}");
+            String::new()
+        })
+    }
+
+    /// A header and a single row, every enemy kind and weapon gets a column even without kills
+    pub fn to_csv(&self) -> String {
+        let entry = &self.entry;
+        let stats = &self.stats;
+        let mut columns: Vec<(String, String)> = vec![
+            ("name".into(), csv_field(&entry.name)),
+            ("score".into(), csv_field(entry.score)),
+            ("level".into(), csv_field(entry.level)),
+            ("date".into(), csv_field(entry.date)),
+            ("seed".into(), csv_field(entry.seed)),
+            ("version".into(), csv_field(&entry.version)),
+            ("difficulty".into(), csv_field(entry.difficulty.name())),
+            ("arena".into(), csv_field(entry.arena.name())),
+            ("character".into(), csv_field(&entry.character)),
+            ("time_alive".into(), csv_field(stats.time_alive)),
+            ("kills".into(), csv_field(stats.kills)),
+            ("damage_dealt".into(), csv_field(stats.damage_dealt)),
+            ("damage_taken".into(), csv_field(stats.damage_taken)),
+            ("distance".into(), csv_field(stats.distance)),
+            (
+                "projectiles_fired".into(),
+                csv_field(stats.projectiles_fired),
+            ),
+            ("experience".into(), csv_field(stats.experience)),
+        ];
+        for kind in EnemyKind::ALL {
+            let kills = stats.kills_by_enemy.get(&kind).copied().unwrap_or(0);
+            columns.push((format!("kills_{kind:?}"), csv_field(kills)));
+        }
+        for weapon in Weapon::ALL {
+            let kills = stats.kills_by_weapon.get(&weapon).copied().unwrap_or(0);
+            columns.push((format!("kills_{weapon:?}"), csv_field(kills)));
+        }
+        let (header, row): (Vec<_>, Vec<_>) = columns.into_iter().unzip();
+        format!("{}\n{}\n", header.join(","), row.join(","))
+    }
+
+    /// Saves the report next to the settings and returns the name of the file
+    pub fn export(&self, format: ExportFormat) -> String {
+        let name = format!(
+            "run-{}-{}.{}",
+            self.entry.date,
+            self.entry.seed,
+            format.extension()
+        );
+        let contents = match format {
+            ExportFormat::Json => self.to_json(),
+            ExportFormat::Csv => self.to_csv(),
+        };
+        storage::save(&name, &contents);
+        info!("Exported the run to {name}");
+        name
+    }
+}
+
+/// Sent once a run is over with everything that was recorded about it
+#[derive(Event)]
+pub struct RunFinished(pub RunReport);
+
+impl Plugin for RunPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Run>()
+            .add_event::<RunFinished>()
+            .init_resource::<RunStats>()
+            .add_systems(OnEnter(GameState::Playing), start_run)
+            // After everything that happened this frame, also on the frame the run ends
+            .add_systems(
+                PostUpdate,
+                update_run_stats.run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn start_run(
+    mut run: ResMut<Run>,
+    settings: Res<Settings>,
+    data: Res<DataAssets>,
+    characters: Res<Assets<CharacterList>>,
+    mut spawn_timer: ResMut<SpawnTimer>,
+) {
+    let seed = rand::thread_rng().gen();
+    *run = Run {
+        seed,
+        difficulty: settings.difficulty,
+        arena: Arena::default(),
+        character: selected_character(&settings, &data, &characters)
+            .map(|character| character.id.clone())
+            .unwrap_or_default(),
+        rng: StdRng::seed_from_u64(seed as u64),
+    };
+    spawn_timer.scale(run.difficulty.spawn_interval());
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_run_stats(
+    time: Res<Time>,
+    mut stats: ResMut<RunStats>,
+    player_query: Query<&Transform, With<Player>>,
+    fired_query: Query<(), Added<Projectile>>,
+    mut killed_events: EventReader<EnemyKilled>,
+    mut enemy_damaged_events: EventReader<EnemyDamaged>,
+    mut player_damaged_events: EventReader<PlayerDamaged>,
+    mut collected_events: EventReader<ExperienceCollected>,
+) {
+    stats.time_alive += time.delta_seconds();
+    for killed in killed_events.read() {
+        stats.kills += 1;
+        *stats.kills_by_enemy.entry(killed.kind).or_default() += 1;
+        if let Some(weapon) = killed.weapon {
+            *stats.kills_by_weapon.entry(weapon).or_default() += 1;
+        }
+    }
+    stats.damage_dealt += enemy_damaged_events
+        .read()
+        .map(|damaged| damaged.amount)
+        .sum::<f32>();
+    stats.damage_taken += player_damaged_events
+        .read()
+        .map(|damaged| damaged.amount)
+        .sum::<f32>();
+    stats.experience += collected_events
+        .read()
+        .map(|ExperienceCollected(experience)| experience)
+        .sum::<i32>();
+    stats.projectiles_fired += fired_query.iter().count() as u32;
+    if let Ok(transform) = player_query.get_single() {
+        let position = transform.translation.truncate();
+        if let Some(last_position) = stats.last_position {
+            stats.distance += position.distance(last_position);
+        }
+        stats.last_position = Some(position);
+    }
+}
+
+#[cfg(all(test, feature = "export"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("Ninja"), "Ninja");
+        assert_eq!(csv_field(12), "12");
+        assert_eq!(csv_field("Ninja, Jr."), "\"Ninja, Jr.\"");
+        assert_eq!(csv_field("The \"Ninja\""), "\"The \"\"Ninja\"\"\"");
+        assert_eq!(csv_field("Nin\nja"), "\"Nin\nja\"");
+        assert_eq!(csv_field("Nin\rja"), "\"Nin\rja\"");
+    }
+
+    #[test]
+    fn odd_names_stay_in_their_column() {
+        let report = RunReport {
+            entry: LeaderboardEntry {
+                name: "A \"b\",\nc".to_string(),
+                score: 10,
+                level: 2,
+                survival_time: 30.,
+                kills: 3,
+                date: 1,
+                seed: 4,
+                version: "0.2.0".to_string(),
+                difficulty: Difficulty::Hard,
+                arena: Arena::Desert,
+                character: "ninja".to_string(),
+            },
+            stats: RunStats::default(),
+        };
+        let csv = report.to_csv();
+        let (header, row) = csv.split_once('\n').unwrap();
+        assert!(header.starts_with("name,score,"));
+        assert!(row.starts_with("\"A \"\"b\"\",\nc\",10,2,1,4,0.2.0,Hard,Desert,ninja,"));
+        // Once the quoted name is taken out, every other column is plain
+        let rest = row.trim_end().strip_prefix("\"A \"\"b\"\",\nc\",").unwrap();
+        assert_eq!(rest.split(',').count() + 1, header.split(',').count());
+    }
+}
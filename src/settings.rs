@@ -0,0 +1,388 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode, WindowResized};
+use serde::{Deserialize, Serialize};
+
+use crate::run::Difficulty;
+use crate::storage;
+
+const SETTINGS_FILE: &str = "settings.toml";
+
+pub const RESOLUTIONS: [(u32, u32); 4] = [(1280, 720), (1600, 900), (1920, 1080), (1024, 768)];
+pub const UI_SCALES: [f32; 4] = [0.75, 1., 1.25, 1.5];
+// The interface is laid out for windows this tall and grows or shrinks with the window
+const UI_REFERENCE_HEIGHT: f32 = 720.;
+// Settings are saved once they stayed the same for this many seconds
+const SAVE_DELAY: f32 = 1.;
+
+pub struct SettingsPlugin;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorPalette {
+    #[default]
+    Default,
+    /// Colours that stay distinguishable with the common kinds of colour blindness
+    ColorblindSafe,
+}
+
+/// How big the minimap in the corner is, if it is shown at all
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MinimapSize {
+    Off,
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+
+impl MinimapSize {
+    /// The width of the minimap, the height follows the shape of the arena
+    pub fn width(&self) -> Option<f32> {
+        match self {
+            MinimapSize::Off => None,
+            MinimapSize::Small => Some(120.),
+            MinimapSize::Medium => Some(170.),
+            MinimapSize::Large => Some(230.),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MinimapSize::Off => "Off",
+            MinimapSize::Small => "Small",
+            MinimapSize::Medium => "Medium",
+            MinimapSize::Large => "Large",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            MinimapSize::Off => MinimapSize::Small,
+            MinimapSize::Small => MinimapSize::Medium,
+            MinimapSize::Medium => MinimapSize::Large,
+            MinimapSize::Large => MinimapSize::Off,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    /// Translates one of the menu texts, anything without a translation is shown in English
+    pub fn tr(&self, text: &'static str) -> &'static str {
+        match self {
+            Language::English => text,
+            Language::Spanish => match text {
+                "Play" => "Jugar",
+                "Quit" => "Salir",
+                "Settings" => "Ajustes",
+                "Back" => "Volver",
+                "Leaderboard" => "Clasificación",
+                "Credits" => "Créditos",
+                "Made with Bevy" => "Hecho con Bevy",
+                "Source" => "Código fuente",
+                "Difficulty" => "Dificultad",
+                "Easy" => "Fácil",
+                "Normal" => "Normal",
+                "Hard" => "Difícil",
+                "Arena" => "Arena",
+                "Desert" => "Desierto",
+                "Name" => "Nombre",
+                "Score" => "Puntos",
+                "Level" => "Nivel",
+                "Time" => "Tiempo",
+                "Kills" => "Bajas",
+                "Date" => "Fecha",
+                "Seed" => "Semilla",
+                "Version" => "Versión",
+                "No runs yet" => "Aún no hay partidas",
+                "Leaderboard rank" => "Puesto en la clasificación",
+                "Runs" => "Partidas",
+                "Everyone" => "Todos",
+                "This device" => "Este dispositivo",
+                "Loading..." => "Cargando...",
+                "The leaderboard server can't be reached" => {
+                    "No se puede conectar con el servidor de clasificación"
+                }
+                "runs are waiting to be sent" => "partidas esperan a ser enviadas",
+                "Profiles" => "Perfiles",
+                "New profile..." => "Nuevo perfil...",
+                "No profiles yet" => "Aún no hay perfiles",
+                "Best score" => "Mejor puntuación",
+                "Anonymous" => "Anónimo",
+                "Enter a name" => "Escribe un nombre",
+                "That name is too long" => "Ese nombre es demasiado largo",
+                "Names can't contain invisible characters" => {
+                    "Los nombres no pueden tener caracteres invisibles"
+                }
+                "Last run" => "Última partida",
+                "Time alive" => "Tiempo con vida",
+                "Damage dealt" => "Daño causado",
+                "Damage taken" => "Daño recibido",
+                "Distance" => "Distancia",
+                "Projectiles fired" => "Proyectiles lanzados",
+                "Experience" => "Experiencia",
+                "Ninja" => "Ninja",
+                "Pack hunter" => "Cazador en manada",
+                "Shuriken" => "Shuriken",
+                "Homing shuriken" => "Shuriken teledirigido",
+                "Grenade" => "Granada",
+                "Export JSON" => "Exportar JSON",
+                "Export CSV" => "Exportar CSV",
+                "Saved to" => "Guardado en",
+                "Achievements" => "Logros",
+                "Achievement unlocked" => "Logro desbloqueado",
+                "Gold earned" => "Oro ganado",
+                "Shop" => "Tienda",
+                "Gold" => "Oro",
+                "Max" => "Máx.",
+                "Max health" => "Salud máxima",
+                "Move speed" => "Velocidad",
+                "Starting weapon level" => "Nivel inicial del arma",
+                "Experience gain" => "Experiencia ganada",
+                "Magnet range" => "Alcance del imán",
+                "+1 health" => "+1 de salud",
+                "+8% speed" => "+8% de velocidad",
+                "+1 projectile per throw" => "+1 proyectil por lanzamiento",
+                "+15% experience" => "+15% de experiencia",
+                "+30 pickup range" => "+30 de alcance al recoger",
+                "Health" => "Salud",
+                "Interface size" => "Tamaño de la interfaz",
+                "Wave" => "Oleada",
+                "Might" => "Fuerza",
+                "Haste" => "Prisa",
+                "Invulnerability" => "Invulnerabilidad",
+                "Bomb" => "Bomba",
+                "Magnet" => "Imán",
+                "Chest" => "Cofre",
+                "Tabi boots" => "Botas tabi",
+                "Hourglass" => "Reloj de arena",
+                "Scroll of reach" => "Pergamino de alcance",
+                "Extra pouch" => "Bolsa extra",
+                "Chainmail" => "Cota de malla",
+                "Green tea" => "Té verde",
+                "Blade storm" => "Tormenta de cuchillas",
+                "Cluster missiles" => "Misiles de racimo",
+                "+10% speed" => "+10% de velocidad",
+                "-8% cooldown" => "-8% de recarga",
+                "+10% area" => "+10% de área",
+                "-0.1 damage taken" => "-0,1 de daño recibido",
+                "+0.05 health per second" => "+0,05 de salud por segundo",
+                "Minimap" => "Minimapa",
+                "Minimap opacity" => "Opacidad del minimapa",
+                "Small" => "Pequeño",
+                "Medium" => "Mediano",
+                "Large" => "Grande",
+                "Choose a character" => "Elige un personaje",
+                "Character" => "Personaje",
+                "Weapon" => "Arma",
+                "Passive" => "Pasiva",
+                "Regeneration" => "Regeneración",
+                "Barrier" => "Barrera",
+                "Frenzy" => "Frenesí",
+                "Master volume" => "Volumen general",
+                "Music volume" => "Volumen de la música",
+                "Effects volume" => "Volumen de efectos",
+                "Window" => "Ventana",
+                "Windowed" => "En ventana",
+                "Fullscreen" => "Pantalla completa",
+                "Resolution" => "Resolución",
+                "VSync" => "Sincronización vertical",
+                "Screen shake" => "Temblor de pantalla",
+                "Damage numbers" => "Números de daño",
+                "Hit flash" => "Destello al golpear",
+                "Colours" => "Colores",
+                "Colourblind" => "Daltónico",
+                "Default" => "Normal",
+                "Language" => "Idioma",
+                "On" => "Sí",
+                "Off" => "No",
+                _ => text,
+            },
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+}
+
+/// Player preferences, loaded at startup and saved whenever they change
+#[derive(Resource, Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Settings {
+    /// Volumes are between 0 and 1, music and sound effects are scaled by the master volume
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub fullscreen: bool,
+    /// Index into [`RESOLUTIONS`]
+    pub resolution: usize,
+    pub vsync: bool,
+    pub screen_shake: bool,
+    pub damage_numbers: bool,
+    pub hit_flash: bool,
+    pub palette: ColorPalette,
+    pub language: Language,
+    /// Used for the next run
+    pub difficulty: Difficulty,
+    /// The id of the character for the next run, the first one when empty or unknown
+    pub character: String,
+    /// Multiplies the size of the interface on top of the scaling with the window size
+    pub ui_scale: f32,
+    pub minimap: MinimapSize,
+    /// Between 0 and 1
+    pub minimap_opacity: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            master_volume: 1.,
+            music_volume: 0.5,
+            sfx_volume: 0.8,
+            fullscreen: false,
+            resolution: 0,
+            vsync: true,
+            screen_shake: true,
+            damage_numbers: true,
+            hit_flash: true,
+            palette: ColorPalette::Default,
+            language: Language::English,
+            difficulty: Difficulty::Normal,
+            character: String::new(),
+            ui_scale: 1.,
+            minimap: MinimapSize::Medium,
+            minimap_opacity: 0.75,
+        }
+    }
+}
+
+impl Settings {
+    fn load() -> Self {
+        let Some(contents) = storage::load(SETTINGS_FILE) else {
+            return Settings::default();
+        };
+        toml::from_str(&contents).unwrap_or_else(|error| {
+            warn!("Ignoring invalid {SETTINGS_FILE}: {error}");
+            Settings::default()
+        })
+    }
+
+    fn save(&self) {
+        match toml::to_string(self) {
+            Ok(contents) => storage::save(SETTINGS_FILE, &contents),
+            Err(error) => warn!("Failed to save settings: {error}"),
+        }
+    }
+
+    pub fn resolution(&self) -> (u32, u32) {
+        RESOLUTIONS
+            .get(self.resolution)
+            .copied()
+            .unwrap_or(RESOLUTIONS[0])
+    }
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load())
+            .add_systems(Update, (apply_window_settings, apply_ui_scale))
+            // Last, so the settings are still saved in the frame the game quits
+            .add_systems(Last, save_settings);
+    }
+}
+
+/// The settings that change the window
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct WindowSettings {
+    fullscreen: bool,
+    resolution: usize,
+    vsync: bool,
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut applied: Local<Option<WindowSettings>>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let wanted = WindowSettings {
+        fullscreen: settings.fullscreen,
+        resolution: settings.resolution,
+        vsync: settings.vsync,
+    };
+    // Most settings have nothing to do with the window, which shouldn't be touched for them
+    if *applied == Some(wanted) {
+        return;
+    }
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+    *applied = Some(wanted);
+    window.mode = if settings.fullscreen {
+        WindowMode::BorderlessFullscreen
+    } else {
+        WindowMode::Windowed
+    };
+    // The browser decides the size of the canvas
+    if cfg!(not(target_arch = "wasm32")) {
+        let (width, height) = settings.resolution();
+        window.resolution.set(width as f32, height as f32);
+    }
+    window.present_mode = if settings.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+}
+
+fn apply_ui_scale(
+    settings: Res<Settings>,
+    mut ui_scale: ResMut<UiScale>,
+    mut resized_events: EventReader<WindowResized>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let resized = resized_events.read().count() > 0;
+    if !settings.is_changed() && !resized {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let scale = settings.ui_scale * window.height() / UI_REFERENCE_HEIGHT;
+    if ui_scale.0 != scale {
+        ui_scale.0 = scale;
+    }
+}
+
+/// Saves the settings once they stop changing, or right away when the game quits
+fn save_settings(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut delay: Local<Option<Timer>>,
+    mut exit_events: EventReader<AppExit>,
+) {
+    // Nothing to save when the settings were just loaded
+    if settings.is_changed() && !settings.is_added() {
+        *delay = Some(Timer::from_seconds(SAVE_DELAY, TimerMode::Once));
+    }
+    let quitting = exit_events.read().count() > 0;
+    let Some(timer) = delay.as_mut() else {
+        return;
+    };
+    if timer.tick(time.delta()).finished() || quitting {
+        settings.save();
+        *delay = None;
+    }
+}
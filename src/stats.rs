@@ -0,0 +1,265 @@
+//! Everything that can be made better or worse during a run is a [`Stat`]. Entities keep base
+//! values and a list of modifiers in [`Stats`], and systems read the derived value instead of
+//! working it out themselves.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::character::{Character, Passive};
+use crate::item::PassiveItem;
+use crate::player::Player;
+use crate::status::StatusEffects;
+use crate::GameState;
+
+// Units per second the player moves at level 0
+const PLAYER_SPEED: f32 = 150.;
+// Units per second every level adds to the player's speed
+const PLAYER_SPEED_PER_LEVEL: f32 = 10.;
+// Units per second an enemy moves per level
+const ENEMY_SPEED: f32 = 20.;
+
+pub struct StatsPlugin;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Stat {
+    MaxHealth,
+    /// Units per second
+    MoveSpeed,
+    /// Multiplies the damage dealt
+    Damage,
+    /// Multiplies the time between two throws
+    Cooldown,
+    /// Multiplies the size of projectiles and explosions
+    Area,
+    /// Thrown on top of what every weapon throws on its own
+    Projectiles,
+    /// Taken off the damage of every hit
+    Armor,
+    /// Health healed per second
+    Regeneration,
+    /// Multiplies the experience from orbs
+    ExperienceGain,
+    /// Experience orbs closer than this fly to the player
+    MagnetRange,
+}
+
+impl Stat {
+    /// What the stat is when nothing set a base value for it
+    fn default_base(&self) -> f32 {
+        match self {
+            Stat::MaxHealth | Stat::Damage | Stat::Cooldown | Stat::Area | Stat::ExperienceGain => {
+                1.
+            }
+            Stat::MoveSpeed
+            | Stat::Projectiles
+            | Stat::Armor
+            | Stat::Regeneration
+            | Stat::MagnetRange => 0.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Modifier {
+    /// Added to the base value before anything multiplies it
+    Add(f32),
+    /// Multiplies the base value with everything added to it
+    Multiply(f32),
+}
+
+/// Where a modifier comes from, so it can be replaced when that changes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModifierSource {
+    Character,
+    /// Bought in the shop, or given by a chest during the run
+    Upgrades,
+    /// The level of the player or enemy
+    Level,
+    Status,
+    Item(PassiveItem),
+}
+
+/// The base values of every stat and what modifies them
+#[derive(Component, Default, Debug)]
+pub struct Stats {
+    base: HashMap<Stat, f32>,
+    modifiers: Vec<(ModifierSource, Stat, Modifier)>,
+}
+
+impl Stats {
+    /// What the character starts every run with
+    pub fn for_character(character: &Character) -> Self {
+        let base = character.stats;
+        let mut stats = Stats::default()
+            .with_base(Stat::MaxHealth, base.max_health)
+            .with_base(Stat::MoveSpeed, PLAYER_SPEED)
+            .with_base(Stat::ExperienceGain, base.experience_gain)
+            .with_base(Stat::MagnetRange, base.magnet_range);
+        stats.add(
+            ModifierSource::Character,
+            Stat::MoveSpeed,
+            Modifier::Multiply(base.move_speed),
+        );
+        if let Some(Passive::Regeneration(per_second)) = character.passive {
+            stats.add(
+                ModifierSource::Character,
+                Stat::Regeneration,
+                Modifier::Add(per_second),
+            );
+        }
+        stats
+    }
+
+    /// Stronger enemies are faster, even the weakest ones walk in at the speed of a level 1 enemy
+    pub fn for_enemy(level: i32) -> Self {
+        let mut stats = Stats::default().with_base(Stat::MoveSpeed, ENEMY_SPEED);
+        stats.add(
+            ModifierSource::Level,
+            Stat::MoveSpeed,
+            Modifier::Multiply(level.max(1) as f32),
+        );
+        stats
+    }
+
+    pub fn with_base(mut self, stat: Stat, value: f32) -> Self {
+        self.base.insert(stat, value);
+        self
+    }
+
+    pub fn base(&self, stat: Stat) -> f32 {
+        self.base
+            .get(&stat)
+            .copied()
+            .unwrap_or_else(|| stat.default_base())
+    }
+
+    /// The base value with everything added to it, then multiplied by every multiplier
+    pub fn get(&self, stat: Stat) -> f32 {
+        let (added, multiplier) = self
+            .modifiers
+            .iter()
+            .filter(|(_, modified, _)| *modified == stat)
+            .fold(
+                (0., 1.),
+                |(added, multiplier), (_, _, modifier)| match modifier {
+                    Modifier::Add(amount) => (added + amount, multiplier),
+                    Modifier::Multiply(factor) => (added, multiplier * factor),
+                },
+            );
+        (self.base(stat) + added) * multiplier
+    }
+
+    /// Adds one more modifier from the source, next to the ones it already has
+    pub fn add(&mut self, source: ModifierSource, stat: Stat, modifier: Modifier) {
+        self.modifiers.push((source, stat, modifier));
+    }
+
+    /// Replaces every modifier from the source
+    pub fn set(
+        &mut self,
+        source: ModifierSource,
+        modifiers: impl IntoIterator<Item = (Stat, Modifier)>,
+    ) {
+        self.modifiers.retain(|(from, _, _)| *from != source);
+        self.modifiers.extend(
+            modifiers
+                .into_iter()
+                .map(|(stat, modifier)| (source, stat, modifier)),
+        );
+    }
+}
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (apply_level_modifiers, apply_status_modifiers).run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// The player gets faster with every level
+fn apply_level_modifiers(mut player_query: Query<(&mut Stats, Ref<Player>)>) {
+    for (mut stats, player) in &mut player_query {
+        if player.is_changed() {
+            let speed = player.level.value as f32 * PLAYER_SPEED_PER_LEVEL;
+            stats.set(
+                ModifierSource::Level,
+                [(Stat::MoveSpeed, Modifier::Add(speed))],
+            );
+        }
+    }
+}
+
+/// Only when the effects change, so the stats aren't marked as changed every frame
+fn apply_status_modifiers(
+    mut stats_query: Query<(&mut Stats, &StatusEffects), Changed<StatusEffects>>,
+) {
+    for (mut stats, statuses) in &mut stats_query {
+        stats.set(
+            ModifierSource::Status,
+            [
+                (
+                    Stat::MoveSpeed,
+                    Modifier::Multiply(statuses.speed_multiplier()),
+                ),
+                (
+                    Stat::Damage,
+                    Modifier::Multiply(statuses.might_multiplier()),
+                ),
+            ],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_before_multiplying_whatever_the_order() {
+        let mut stats = Stats::default().with_base(Stat::MoveSpeed, 10.);
+        stats.add(
+            ModifierSource::Character,
+            Stat::MoveSpeed,
+            Modifier::Multiply(2.),
+        );
+        stats.add(ModifierSource::Level, Stat::MoveSpeed, Modifier::Add(5.));
+        assert_eq!(stats.get(Stat::MoveSpeed), 30.);
+        // Other stats are left alone
+        assert_eq!(stats.get(Stat::MaxHealth), 1.);
+    }
+
+    #[test]
+    fn combines_every_source() {
+        let mut stats = Stats::default().with_base(Stat::Damage, 2.);
+        stats.add(
+            ModifierSource::Character,
+            Stat::Damage,
+            Modifier::Multiply(1.5),
+        );
+        stats.set(
+            ModifierSource::Status,
+            [
+                (Stat::Damage, Modifier::Multiply(2.)),
+                (Stat::Damage, Modifier::Add(1.)),
+            ],
+        );
+        stats.add(
+            ModifierSource::Item(PassiveItem::Area),
+            Stat::Damage,
+            Modifier::Add(1.),
+        );
+        assert_eq!(stats.get(Stat::Damage), 12.);
+
+        // Setting a source again replaces only its own modifiers
+        stats.set(
+            ModifierSource::Status,
+            [(Stat::Damage, Modifier::Multiply(1.))],
+        );
+        assert_eq!(stats.get(Stat::Damage), 4.5);
+        stats.set(ModifierSource::Status, []);
+        assert_eq!(stats.get(Stat::Damage), 4.5);
+    }
+}
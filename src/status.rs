@@ -0,0 +1,328 @@
+use bevy::prelude::*;
+
+use crate::enemy::{Enemy, EnemyDamaged};
+use crate::player::{Player, PlayerDamaged};
+use crate::settings::{ColorPalette, Settings};
+use crate::GameState;
+
+// Damage over time effects deal their damage in chunks this many seconds apart
+const DAMAGE_TICK: f32 = 0.5;
+
+pub struct StatusPlugin;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusKind {
+    /// Damage over time, reapplying refreshes it
+    Burn,
+    /// Damage over time, every application stacks
+    Poison,
+    /// Reduces speed by the magnitude, between 0 and 1
+    Slow,
+    /// Can't move at all
+    Freeze,
+    /// Can't move or change direction
+    Stun,
+    /// Takes more damage, scaled by 1 + magnitude
+    Vulnerability,
+    /// Increases speed by the magnitude
+    Haste,
+    /// Absorbs the next hit
+    Shield,
+    /// Deals more damage, scaled by 1 + magnitude
+    Might,
+}
+
+/// What happens when an effect is applied while the same kind is already active
+enum Stacking {
+    /// Keep a single effect with the highest magnitude and the longest remaining time
+    Refresh,
+    /// Every application is tracked separately, up to this many at once
+    Stack(usize),
+}
+
+impl StatusKind {
+    fn stacking(&self) -> Stacking {
+        match self {
+            StatusKind::Poison => Stacking::Stack(5),
+            _ => Stacking::Refresh,
+        }
+    }
+
+    fn tint(&self, palette: ColorPalette) -> Color {
+        if palette == ColorPalette::ColorblindSafe {
+            // Based on the Okabe-Ito palette
+            return match self {
+                StatusKind::Burn => Color::rgb(0.84, 0.37, 0.),
+                StatusKind::Poison => Color::rgb(0., 0.62, 0.45),
+                StatusKind::Slow => Color::rgb(0., 0.45, 0.7),
+                StatusKind::Freeze => Color::rgb(0.34, 0.71, 0.91),
+                StatusKind::Stun => Color::rgb(0.94, 0.89, 0.26),
+                StatusKind::Vulnerability => Color::rgb(0.8, 0.47, 0.65),
+                StatusKind::Haste => Color::rgb(0.9, 0.62, 0.),
+                StatusKind::Shield => Color::rgb(0.6, 0.6, 0.6),
+                StatusKind::Might => Color::rgb(0.35, 0.35, 0.35),
+            };
+        }
+        match self {
+            StatusKind::Burn => Color::rgb(1., 0.5, 0.2),
+            StatusKind::Poison => Color::rgb(0.5, 1., 0.4),
+            StatusKind::Slow => Color::rgb(0.6, 0.6, 1.),
+            StatusKind::Freeze => Color::rgb(0.6, 0.9, 1.),
+            StatusKind::Stun => Color::rgb(1., 1., 0.4),
+            StatusKind::Vulnerability => Color::rgb(0.8, 0.4, 1.),
+            StatusKind::Haste => Color::rgb(0.4, 1., 1.),
+            StatusKind::Shield => Color::GOLD,
+            StatusKind::Might => Color::rgb(1., 0.3, 0.3),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// Damage per second for damage over time, a fraction for the modifiers, unused otherwise
+    pub magnitude: f32,
+    pub duration: Timer,
+    tick: Timer,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, magnitude: f32, duration: f32) -> Self {
+        StatusEffect {
+            kind,
+            magnitude,
+            duration: Timer::from_seconds(duration, TimerMode::Once),
+            tick: Timer::from_seconds(DAMAGE_TICK, TimerMode::Repeating),
+        }
+    }
+
+    fn is_damage_over_time(&self) -> bool {
+        matches!(self.kind, StatusKind::Burn | StatusKind::Poison)
+    }
+}
+
+/// All the buffs and debuffs currently active on an enemy or the player
+#[derive(Component, Default, Debug)]
+pub struct StatusEffects(Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: StatusEffect) {
+        match effect.kind.stacking() {
+            Stacking::Refresh => {
+                if let Some(active) = self.0.iter_mut().find(|active| active.kind == effect.kind) {
+                    active.magnitude = active.magnitude.max(effect.magnitude);
+                    if effect.duration.remaining() > active.duration.remaining() {
+                        active.duration = effect.duration;
+                    }
+                } else {
+                    self.0.push(effect);
+                }
+            }
+            Stacking::Stack(max) => {
+                let stacks = self.0.iter().filter(|active| active.kind == effect.kind);
+                if stacks.count() >= max {
+                    // Replace the stack that is closest to running out
+                    if let Some(oldest) = self
+                        .0
+                        .iter_mut()
+                        .filter(|active| active.kind == effect.kind)
+                        .min_by(|a, b| a.duration.remaining().cmp(&b.duration.remaining()))
+                    {
+                        *oldest = effect;
+                    }
+                } else {
+                    self.0.push(effect);
+                }
+            }
+        }
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.0.iter().any(|effect| effect.kind == kind)
+    }
+
+    /// Removes one effect of the given kind, returning whether there was one
+    pub fn consume(&mut self, kind: StatusKind) -> bool {
+        if let Some(index) = self.0.iter().position(|effect| effect.kind == kind) {
+            self.0.remove(index);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn strongest(&self, kind: StatusKind) -> f32 {
+        self.0
+            .iter()
+            .filter(|effect| effect.kind == kind)
+            .map(|effect| effect.magnitude)
+            .fold(0., f32::max)
+    }
+
+    pub fn can_move(&self) -> bool {
+        !self.has(StatusKind::Freeze) && !self.has(StatusKind::Stun)
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        if !self.can_move() {
+            return 0.;
+        }
+        (1. - self.strongest(StatusKind::Slow)).max(0.) * (1. + self.strongest(StatusKind::Haste))
+    }
+
+    /// How much more damage is taken
+    pub fn damage_multiplier(&self) -> f32 {
+        1. + self.strongest(StatusKind::Vulnerability)
+    }
+
+    /// How much more damage is dealt
+    pub fn might_multiplier(&self) -> f32 {
+        1. + self.strongest(StatusKind::Might)
+    }
+
+    /// The tint of the most recently applied effect, if any
+    pub fn tint(&self, palette: ColorPalette) -> Option<Color> {
+        self.0.last().map(|effect| effect.kind.tint(palette))
+    }
+}
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (tick_status_effects, tint_status_effects)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Runs the timers down and deals damage over time, to enemies and the player alike
+fn tick_status_effects(
+    time: Res<Time>,
+    mut status_query: Query<(
+        Entity,
+        &Transform,
+        &mut StatusEffects,
+        Option<&mut Enemy>,
+        Option<&mut Player>,
+    )>,
+    mut damaged_events: EventWriter<EnemyDamaged>,
+    mut player_damaged_events: EventWriter<PlayerDamaged>,
+) {
+    for (entity, transform, mut statuses, mut enemy, mut player) in &mut status_query {
+        let multiplier = statuses.damage_multiplier();
+        // Ticking the timers doesn't count as a change, only effects running out do
+        let effects = &mut statuses.bypass_change_detection().0;
+        for effect in effects.iter_mut() {
+            effect.duration.tick(time.delta());
+            if !effect.is_damage_over_time() {
+                continue;
+            }
+            let ticks = effect.tick.tick(time.delta()).times_finished_this_tick();
+            if ticks == 0 {
+                continue;
+            }
+            let amount = effect.magnitude * DAMAGE_TICK * ticks as f32 * multiplier;
+            if let Some(enemy) = enemy.as_mut() {
+                enemy.health -= amount;
+                damaged_events.send(EnemyDamaged {
+                    entity,
+                    position: transform.translation.truncate(),
+                    amount,
+                });
+            }
+            if let Some(player) = player.as_mut().filter(|player| player.health > 0.) {
+                player.health -= amount;
+                player_damaged_events.send(PlayerDamaged { amount });
+            }
+        }
+        if effects.iter().any(|effect| effect.duration.finished()) {
+            statuses.0.retain(|effect| !effect.duration.finished());
+        }
+    }
+}
+
+pub fn tint_status_effects(
+    settings: Res<Settings>,
+    mut status_query: Query<(&StatusEffects, &mut Sprite), Changed<StatusEffects>>,
+) {
+    for (statuses, mut sprite) in &mut status_query {
+        sprite.color = statuses.tint(settings.palette).unwrap_or(Color::WHITE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn count(statuses: &StatusEffects, kind: StatusKind) -> usize {
+        statuses
+            .0
+            .iter()
+            .filter(|effect| effect.kind == kind)
+            .count()
+    }
+
+    #[test]
+    fn reapplying_keeps_the_strongest_and_longest() {
+        let mut statuses = StatusEffects::default();
+        statuses.apply(StatusEffect::new(StatusKind::Slow, 0.5, 1.));
+        statuses.apply(StatusEffect::new(StatusKind::Slow, 0.2, 3.));
+        assert_eq!(count(&statuses, StatusKind::Slow), 1);
+        assert_eq!(statuses.speed_multiplier(), 0.5);
+        assert_eq!(statuses.0[0].duration.remaining_secs(), 3.);
+        // A shorter one doesn't cut the remaining time
+        statuses.apply(StatusEffect::new(StatusKind::Slow, 0.2, 2.));
+        assert_eq!(statuses.0[0].duration.remaining_secs(), 3.);
+    }
+
+    #[test]
+    fn stacks_up_to_the_maximum_then_replaces_the_oldest() {
+        let mut statuses = StatusEffects::default();
+        for duration in 1..=5 {
+            statuses.apply(StatusEffect::new(StatusKind::Poison, 1., duration as f32));
+        }
+        assert_eq!(count(&statuses, StatusKind::Poison), 5);
+        statuses.apply(StatusEffect::new(StatusKind::Poison, 1., 10.));
+        assert_eq!(count(&statuses, StatusKind::Poison), 5);
+        let mut remaining: Vec<f32> = statuses
+            .0
+            .iter()
+            .map(|effect| effect.duration.remaining_secs())
+            .collect();
+        remaining.sort_by(f32::total_cmp);
+        assert_eq!(remaining, [2., 3., 4., 5., 10.]);
+    }
+
+    #[test]
+    fn effects_run_out_after_their_duration() {
+        let mut app = App::new();
+        app.add_event::<EnemyDamaged>()
+            .add_event::<PlayerDamaged>()
+            .init_resource::<Time>()
+            .add_systems(Update, tick_status_effects);
+        let mut statuses = StatusEffects::default();
+        statuses.apply(StatusEffect::new(StatusKind::Slow, 0.5, 1.));
+        statuses.apply(StatusEffect::new(StatusKind::Haste, 0.5, 2.));
+        let entity = app.world.spawn((Transform::default(), statuses)).id();
+        let advance = |app: &mut App, seconds: f32| {
+            app.world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(seconds));
+            app.update();
+        };
+
+        advance(&mut app, 0.9);
+        let statuses = app.world.get::<StatusEffects>(entity).unwrap();
+        assert!(statuses.has(StatusKind::Slow) && statuses.has(StatusKind::Haste));
+        advance(&mut app, 0.2);
+        let statuses = app.world.get::<StatusEffects>(entity).unwrap();
+        assert!(!statuses.has(StatusKind::Slow) && statuses.has(StatusKind::Haste));
+        advance(&mut app, 1.);
+        let statuses = app.world.get::<StatusEffects>(entity).unwrap();
+        assert!(!statuses.has(StatusKind::Haste));
+    }
+}
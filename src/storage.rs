@@ -0,0 +1,38 @@
+//! Small text files that have to survive restarting the game. They live in the working directory
+//! on desktop and in the browser's localStorage on the web.
+
+use bevy::log::warn;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(name: &str) -> Option<String> {
+    std::fs::read_to_string(name).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(name: &str, contents: &str) {
+    if let Err(error) = std::fs::write(name, contents) {
+        warn!("Failed to save {name}: {error:?}");
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load(name: &str) -> Option<String> {
+    local_storage()?.get_item(name).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(name: &str, contents: &str) {
+    match local_storage() {
+        Some(storage) => {
+            if let Err(error) = storage.set_item(name, contents) {
+                warn!("Failed to save {name}: {error:?}");
+            }
+        }
+        None => warn!("Failed to save {name}: localStorage is not available"),
+    }
+}
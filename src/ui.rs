@@ -1,13 +1,37 @@
+use crate::actions::DashButton;
+use crate::dash::Dash;
+use crate::disguise::{Disguise, MAX_DISGUISE};
+use crate::enemy::Wave;
+use crate::item::{Arsenal, Weapon, WeaponEvolved};
+use crate::loading::TextureAssets;
 use crate::player::Player;
+use crate::run::RunStats;
+use crate::settings::Settings;
+use crate::stats::{Stat, Stats};
 use crate::{menu::Score, GameState};
 use bevy::prelude::*;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef};
 pub struct UIPlugin;
 
 impl Plugin for UIPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), setup_ui)
+        app.add_plugins(UiMaterialPlugin::<CooldownMaterial>::default())
+            .add_systems(OnEnter(GameState::Playing), setup_ui)
             .add_systems(Update, update_score.run_if(in_state(GameState::Playing)))
             .add_systems(Update, update_level.run_if(in_state(GameState::Playing)))
+            .add_systems(Update, update_health.run_if(in_state(GameState::Playing)))
+            .add_systems(Update, update_run_info.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                Update,
+                update_disguise_meter.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, update_dash.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                Update,
+                (fill_weapon_bar, update_weapon_bar)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
             .add_systems(OnExit(GameState::Playing), cleanup_ui);
     }
 }
@@ -16,73 +40,578 @@ impl Plugin for UIPlugin {
 struct UIScore;
 #[derive(Component)]
 struct UILevel;
+/// Fills up with the experience towards the next level
+#[derive(Component)]
+struct UIExperienceBar;
+#[derive(Component)]
+struct UIHealth;
+/// Fills up with the health that is left
+#[derive(Component)]
+struct UIHealthBar;
+#[derive(Component)]
+struct UITimer;
+#[derive(Component)]
+struct UIKills;
+#[derive(Component)]
+struct UIWave;
+/// Fills up with the disguise that is left
+#[derive(Component)]
+struct UIDisguiseMeter;
+/// Covers the part of a node that is still cooling down, see `assets/shaders/cooldown.wgsl`
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+struct CooldownMaterial {
+    #[uniform(0)]
+    color: Color,
+    /// Only x is used, between 0 when the cooldown starts and 1 when it is over
+    #[uniform(0)]
+    progress: Vec4,
+}
+
+impl CooldownMaterial {
+    fn new() -> Self {
+        CooldownMaterial {
+            color: COOLDOWN_COLOR,
+            progress: Vec4::ONE,
+        }
+    }
+}
+
+impl UiMaterial for CooldownMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/cooldown.wgsl".into()
+    }
+}
+
+#[derive(Component)]
+struct UIDashCooldown;
+#[derive(Component)]
+struct UIDashCharges;
+/// Holds a slot for every weapon the player carries
+#[derive(Component)]
+struct UIWeaponBar;
+#[derive(Component)]
+struct UIWeaponCooldown(Weapon);
+#[derive(Component)]
+struct UIWeaponLevel(Weapon);
+#[derive(Component)]
+struct UIWeaponCharges(Weapon);
 #[derive(Component)]
 struct UIHud;
 
-fn setup_ui(mut commands: Commands, score_q: Res<Score>) {
+const DISGUISE_READY_COLOR: Color = Color::rgb(0.3, 0.7, 0.3);
+const DISGUISE_ACTIVE_COLOR: Color = Color::rgb(0.5, 1., 0.5);
+const DISGUISE_COOLDOWN_COLOR: Color = Color::rgb(0.4, 0.4, 0.4);
+const HUD_TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const BAR_BACKGROUND_COLOR: Color = Color::rgba(0.05, 0.05, 0.05, 0.8);
+const HEALTH_COLOR: Color = Color::rgb(0.8, 0.2, 0.2);
+const EXPERIENCE_COLOR: Color = Color::rgb(0.3, 0.6, 1.);
+const COOLDOWN_COLOR: Color = Color::rgba(0., 0., 0., 0.65);
+const SLOT_COLOR: Color = Color::rgba(0.15, 0.15, 0.15, 0.8);
+const SLOT_TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const EMPTY_CHARGES_COLOR: Color = Color::rgb(0.9, 0.3, 0.3);
+// The boots in the icon sheet
+const DASH_ICON: usize = 130;
+
+/// An icon with a cooldown over it and two corners of text
+fn spawn_slot(
+    parent: &mut ChildBuilder,
+    textures: &TextureAssets,
+    icon: usize,
+    cooldown: (Handle<CooldownMaterial>, impl Bundle),
+    level: impl Bundle,
+    charges: impl Bundle,
+) {
+    let corner_text = |position: Style| {
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 16.0,
+                color: SLOT_TEXT_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            ..position
+        })
+    };
+    parent.spawn(AtlasImageBundle {
+        style: Style {
+            width: Val::Percent(80.0),
+            height: Val::Percent(80.0),
+            ..default()
+        },
+        image: textures.icons.clone().into(),
+        texture_atlas: TextureAtlas {
+            layout: textures.icons_layout.clone(),
+            index: icon,
+        },
+        ..default()
+    });
+    parent.spawn((
+        MaterialNodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            material: cooldown.0,
+            ..default()
+        },
+        cooldown.1,
+    ));
+    parent.spawn((
+        corner_text(Style {
+            left: Val::Px(3.0),
+            top: Val::Px(1.0),
+            ..default()
+        }),
+        level,
+    ));
+    parent.spawn((
+        corner_text(Style {
+            right: Val::Px(3.0),
+            bottom: Val::Px(1.0),
+            ..default()
+        }),
+        charges,
+    ));
+}
+
+fn slot_style(size: f32) -> Style {
+    Style {
+        width: Val::Px(size),
+        height: Val::Px(size),
+        margin: UiRect::horizontal(Val::Px(4.0)),
+        border: UiRect::all(Val::Px(2.0)),
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::Center,
+        ..default()
+    }
+}
+
+fn hud_text(text: impl Into<String>, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+        text,
+        TextStyle {
+            font_size,
+            color: HUD_TEXT_COLOR,
+            ..default()
+        },
+    )
+}
+
+/// A bar that fills up from the left, with an optional label on top of it
+fn spawn_bar(
+    parent: &mut ChildBuilder,
+    size: Vec2,
+    color: Color,
+    fill: impl Bundle,
+    label: Option<impl Bundle>,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(size.x),
+                height: Val::Px(size.y),
+                margin: UiRect::bottom(Val::Px(4.0)),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: BAR_BACKGROUND_COLOR.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(0.0),
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: color.into(),
+                    ..default()
+                },
+                fill,
+            ));
+            if let Some(label) = label {
+                parent.spawn((hud_text("", size.y - 2.), label));
+            }
+        });
+}
+
+fn score_label(score: &Score, settings: &Settings) -> String {
+    format!("{} {}", settings.language.tr("Score"), score.score)
+}
+
+fn wave_label(wave: &Wave, settings: &Settings) -> String {
+    format!("{} {}", settings.language.tr("Wave"), wave.0)
+}
+
+fn setup_ui(
+    mut commands: Commands,
+    score_q: Res<Score>,
+    wave: Res<Wave>,
+    settings: Res<Settings>,
+    textures: Res<TextureAssets>,
+    mut materials: ResMut<Assets<CooldownMaterial>>,
+) {
+    // Anchored to the top corners and the middle, the whole interface grows with the window
     commands
         .spawn((
             NodeBundle {
                 style: Style {
-                    flex_direction: FlexDirection::Column,
-                    width: Val::Px(200.0),
-                    height: Val::Px(50.0),
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(0.0),
+                    right: Val::Px(0.0),
+                    top: Val::Px(0.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    justify_content: JustifyContent::SpaceBetween,
                     align_items: AlignItems::Start,
-                    justify_content: JustifyContent::Start,
                     ..default()
                 },
-                background_color: Color::rgb(0.15, 0.15, 0.15).into(),
-                ..Default::default()
+                ..default()
             },
             UIHud,
         ))
         .with_children(|parent| {
-            parent.spawn((
-                TextBundle::from_section(
-                    format!("Score: {}", score_q.score),
-                    TextStyle {
-                        font_size: 18.0,
-                        color: Color::rgb(0.9, 0.9, 0.9),
-                        ..default()
-                    },
-                ),
-                UIScore,
-                UIHud,
-            ));
-            parent.spawn((
-                TextBundle::from_section(
-                    format!("Level: {}, Exp: {}/{}", 1, 0, 10),
-                    TextStyle {
-                        font_size: 18.0,
-                        color: Color::rgb(0.9, 0.9, 0.9),
+            let column = |align_items| NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items,
+                    // The middle column stays centred when the sides differ in width
+                    flex_basis: Val::Px(0.0),
+                    flex_grow: 1.,
+                    ..default()
+                },
+                ..default()
+            };
+            parent
+                .spawn(column(AlignItems::Start))
+                .with_children(|parent| {
+                    spawn_bar(
+                        parent,
+                        Vec2::new(220., 18.),
+                        HEALTH_COLOR,
+                        UIHealthBar,
+                        Some(UIHealth),
+                    );
+                    spawn_bar(
+                        parent,
+                        Vec2::new(220., 14.),
+                        EXPERIENCE_COLOR,
+                        UIExperienceBar,
+                        Some(UILevel),
+                    );
+                    spawn_bar(
+                        parent,
+                        Vec2::new(220., 6.),
+                        DISGUISE_READY_COLOR,
+                        UIDisguiseMeter,
+                        None::<()>,
+                    );
+                });
+            parent
+                .spawn(column(AlignItems::Center))
+                .with_children(|parent| {
+                    parent.spawn((hud_text("00:00", 32.), UITimer));
+                    parent.spawn((hud_text(wave_label(&wave, &settings), 18.), UIWave));
+                });
+            parent
+                .spawn(column(AlignItems::End))
+                .with_children(|parent| {
+                    parent.spawn((hud_text(score_label(&score_q, &settings), 22.), UIScore));
+                    parent.spawn((hud_text("", 18.), UIKills));
+                });
+        });
+    // Doubles as the button to dash with on touch screens
+    commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(20.0),
+                    bottom: Val::Px(20.0),
+                    ..slot_style(80.0)
+                },
+                background_color: SLOT_COLOR.into(),
+                border_color: SLOT_TEXT_COLOR.into(),
+                ..default()
+            },
+            DashButton,
+            UIHud,
+        ))
+        .with_children(|parent| {
+            spawn_slot(
+                parent,
+                &textures,
+                DASH_ICON,
+                (materials.add(CooldownMaterial::new()), UIDashCooldown),
+                (),
+                UIDashCharges,
+            );
+        });
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(0.0),
+                right: Val::Px(0.0),
+                bottom: Val::Px(20.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        },
+        UIWeaponBar,
+        UIHud,
+    ));
+}
+
+/// Adds a slot for every weapon once the player has spawned with them, and again after one of
+/// them evolved
+fn fill_weapon_bar(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    mut materials: ResMut<Assets<CooldownMaterial>>,
+    arsenal_q: Query<Ref<Arsenal>>,
+    bar_q: Query<Entity, With<UIWeaponBar>>,
+    mut evolved_events: EventReader<WeaponEvolved>,
+) {
+    let evolved = evolved_events.read().count() > 0;
+    let (Ok(arsenal), Ok(bar)) = (arsenal_q.get_single(), bar_q.get_single()) else {
+        return;
+    };
+    if !arsenal.is_added() && !evolved {
+        return;
+    }
+    commands
+        .entity(bar)
+        .despawn_descendants()
+        .with_children(|parent| {
+            for state in &arsenal.weapons {
+                let weapon = state.weapon;
+                parent
+                    .spawn(NodeBundle {
+                        style: slot_style(56.0),
+                        background_color: SLOT_COLOR.into(),
+                        border_color: SLOT_TEXT_COLOR.into(),
                         ..default()
-                    },
-                ),
-                UILevel,
-                UIHud,
-            ));
+                    })
+                    .with_children(|parent| {
+                        spawn_slot(
+                            parent,
+                            &textures,
+                            weapon.icon(),
+                            (
+                                materials.add(CooldownMaterial::new()),
+                                UIWeaponCooldown(weapon),
+                            ),
+                            UIWeaponLevel(weapon),
+                            UIWeaponCharges(weapon),
+                        );
+                    });
+            }
         });
 }
 
-fn update_score(mut text_q: Query<&mut Text, With<UIScore>>, score_q: Res<Score>) {
+fn update_weapon_bar(
+    mut materials: ResMut<Assets<CooldownMaterial>>,
+    cooldown_q: Query<(&Handle<CooldownMaterial>, &UIWeaponCooldown)>,
+    mut level_q: Query<(&mut Text, &UIWeaponLevel), Without<UIWeaponCharges>>,
+    mut charges_q: Query<(&mut Text, &UIWeaponCharges), Without<UIWeaponLevel>>,
+    arsenal_q: Query<(&Arsenal, &Player, &Stats), Changed<Arsenal>>,
+) {
+    let Ok((arsenal, player, stats)) = arsenal_q.get_single() else {
+        return;
+    };
+    for state in &arsenal.weapons {
+        let charges = state
+            .weapon
+            .charges(player, stats)
+            .map(|charges| charges.saturating_sub(state.in_flight));
+        // Out of charges counts as cooling down until one comes back
+        let progress = if charges == Some(0) {
+            0.
+        } else {
+            state.cooldown.fraction()
+        };
+        for (handle, UIWeaponCooldown(weapon)) in &cooldown_q {
+            if *weapon != state.weapon {
+                continue;
+            }
+            if let Some(material) = materials.get_mut(handle) {
+                if material.progress.x != progress {
+                    material.progress.x = progress;
+                }
+            }
+        }
+        for (mut text, UIWeaponLevel(weapon)) in &mut level_q {
+            let label = format!("Lv {}", player.weapon_level_of(*weapon));
+            if *weapon == state.weapon && text.sections[0].value != label {
+                text.sections[0].value = label;
+            }
+        }
+        for (mut text, UIWeaponCharges(weapon)) in &mut charges_q {
+            if *weapon != state.weapon {
+                continue;
+            }
+            let label = charges.map_or_else(String::new, |charges| charges.to_string());
+            if text.sections[0].value != label {
+                text.sections[0].value = label;
+                text.sections[0].style.color = if charges == Some(0) {
+                    EMPTY_CHARGES_COLOR
+                } else {
+                    SLOT_TEXT_COLOR
+                };
+            }
+        }
+    }
+}
+
+fn update_score(
+    mut text_q: Query<&mut Text, With<UIScore>>,
+    score_q: Res<Score>,
+    settings: Res<Settings>,
+) {
     if score_q.is_changed() {
         for mut text in text_q.iter_mut() {
-            text.sections[0].value = format!("Score {}", score_q.score)
+            text.sections[0].value = score_label(&score_q, &settings)
         }
     }
 }
+
 fn update_level(
     mut text_q: Query<&mut Text, With<UILevel>>,
-    player_q: Query<&Player>,
-    score_q: Res<Score>,
+    mut bar_q: Query<&mut Style, With<UIExperienceBar>>,
+    player_q: Query<Ref<Player>>,
+    settings: Res<Settings>,
 ) {
-    let player = player_q.single();
-    if score_q.is_changed() {
+    let Ok(player) = player_q.get_single() else {
+        return;
+    };
+    if player.is_changed() {
+        for mut text in text_q.iter_mut() {
+            text.sections[0].value =
+                format!("{} {}", settings.language.tr("Level"), player.level.value);
+        }
+        for mut style in bar_q.iter_mut() {
+            style.width = Val::Percent(player.exp.0 as f32 / player.level.exp_max as f32 * 100.);
+        }
+    }
+}
+
+fn update_health(
+    mut text_q: Query<&mut Text, With<UIHealth>>,
+    mut bar_q: Query<&mut Style, With<UIHealthBar>>,
+    player_q: Query<(Ref<Player>, Ref<Stats>)>,
+) {
+    let Ok((player, stats)) = player_q.get_single() else {
+        return;
+    };
+    if player.is_changed() || stats.is_changed() {
+        let max_health = stats.get(Stat::MaxHealth);
         for mut text in text_q.iter_mut() {
             text.sections[0].value = format!(
-                "Level: {}, Exp: {}/{}",
-                player.level.value, player.exp.0, player.level.exp_max
-            );
+                "{:.0}/{:.0}",
+                // Regeneration heals in fractions, a sliver of health still counts
+                player.health.ceil(),
+                max_health
+            )
+        }
+        for mut style in bar_q.iter_mut() {
+            style.width = Val::Percent(player.health.max(0.) / max_health * 100.);
+        }
+    }
+}
+
+/// The run timer, the kills and the wave
+fn update_run_info(
+    mut timer_q: Query<&mut Text, (With<UITimer>, Without<UIKills>, Without<UIWave>)>,
+    mut kills_q: Query<&mut Text, (With<UIKills>, Without<UIWave>)>,
+    mut wave_q: Query<&mut Text, With<UIWave>>,
+    stats: Res<RunStats>,
+    wave: Res<Wave>,
+    settings: Res<Settings>,
+) {
+    let language = settings.language;
+    if stats.is_changed() {
+        let seconds = stats.time_alive as u32;
+        let timer = format!("{:02}:{:02}", seconds / 60, seconds % 60);
+        let kills = format!("{} {}", language.tr("Kills"), stats.kills);
+        for mut text in timer_q.iter_mut() {
+            if text.sections[0].value != timer {
+                text.sections[0].value = timer.clone();
+            }
+        }
+        for mut text in kills_q.iter_mut() {
+            if text.sections[0].value != kills {
+                text.sections[0].value = kills.clone();
+            }
+        }
+    }
+    if wave.is_changed() {
+        for mut text in wave_q.iter_mut() {
+            text.sections[0].value = wave_label(&wave, &settings);
+        }
+    }
+}
+
+fn update_disguise_meter(
+    mut meter_q: Query<(&mut Style, &mut BackgroundColor), With<UIDisguiseMeter>>,
+    disguise_q: Query<&Disguise>,
+) {
+    let Ok(disguise) = disguise_q.get_single() else {
+        return;
+    };
+    for (mut style, mut color) in meter_q.iter_mut() {
+        style.width = Val::Percent(disguise.meter / MAX_DISGUISE * 100.);
+        *color = if disguise.active {
+            DISGUISE_ACTIVE_COLOR
+        } else if disguise.cooldown > 0. {
+            DISGUISE_COOLDOWN_COLOR
+        } else {
+            DISGUISE_READY_COLOR
+        }
+        .into();
+    }
+}
+
+fn update_dash(
+    mut materials: ResMut<Assets<CooldownMaterial>>,
+    cooldown_q: Query<&Handle<CooldownMaterial>, With<UIDashCooldown>>,
+    mut text_q: Query<&mut Text, With<UIDashCharges>>,
+    dash_q: Query<&Dash>,
+) {
+    let Ok(dash) = dash_q.get_single() else {
+        return;
+    };
+    // Stays clear while there is a charge to dash with
+    let progress = if dash.charges > 0 {
+        1.
+    } else {
+        dash.recharge_progress()
+    };
+    for handle in cooldown_q.iter() {
+        if let Some(material) = materials.get_mut(handle) {
+            if material.progress.x != progress {
+                material.progress.x = progress;
+            }
+        }
+    }
+    for mut text in text_q.iter_mut() {
+        let label = dash.charges.to_string();
+        if text.sections[0].value != label {
+            text.sections[0].value = label;
+            text.sections[0].style.color = if dash.charges == 0 {
+                EMPTY_CHARGES_COLOR
+            } else {
+                SLOT_TEXT_COLOR
+            };
         }
     }
 }
@@ -0,0 +1,242 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::player::Player;
+use crate::run::RunFinished;
+use crate::stats::{Modifier, ModifierSource, Stat, Stats};
+use crate::storage;
+
+const UPGRADES_FILE: &str = "upgrades.toml";
+/// Gold earned for every point scored
+const GOLD_PER_SCORE: f32 = 0.5;
+/// Every level of an upgrade costs this much more than the one before
+const COST_GROWTH: f32 = 1.5;
+
+pub struct UpgradesPlugin;
+
+/// Permanent improvements bought with gold between runs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Upgrade {
+    MaxHealth,
+    MoveSpeed,
+    WeaponLevel,
+    ExperienceGain,
+    MagnetRange,
+}
+
+impl Upgrade {
+    pub const ALL: [Upgrade; 5] = [
+        Upgrade::MaxHealth,
+        Upgrade::MoveSpeed,
+        Upgrade::WeaponLevel,
+        Upgrade::ExperienceGain,
+        Upgrade::MagnetRange,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Upgrade::MaxHealth => "Max health",
+            Upgrade::MoveSpeed => "Move speed",
+            Upgrade::WeaponLevel => "Starting weapon level",
+            Upgrade::ExperienceGain => "Experience gain",
+            Upgrade::MagnetRange => "Magnet range",
+        }
+    }
+
+    /// What one level gives, in English so it can be translated
+    pub fn description(&self) -> &'static str {
+        match self {
+            Upgrade::MaxHealth => "+1 health",
+            Upgrade::MoveSpeed => "+8% speed",
+            Upgrade::WeaponLevel => "+1 projectile per throw",
+            Upgrade::ExperienceGain => "+15% experience",
+            Upgrade::MagnetRange => "+30 pickup range",
+        }
+    }
+
+    pub fn max_level(&self) -> u32 {
+        match self {
+            Upgrade::WeaponLevel => 3,
+            _ => 5,
+        }
+    }
+
+    fn base_cost(&self) -> u32 {
+        match self {
+            Upgrade::MaxHealth => 30,
+            Upgrade::MoveSpeed => 20,
+            Upgrade::WeaponLevel => 50,
+            Upgrade::ExperienceGain => 25,
+            Upgrade::MagnetRange => 15,
+        }
+    }
+
+    /// The price of the next level when `level` levels are already bought
+    pub fn cost(&self, level: u32) -> u32 {
+        (self.base_cost() as f32 * COST_GROWTH.powi(level as i32)).round() as u32
+    }
+
+    /// One more level for the rest of the run, on the same curve as the bought ones
+    pub fn grant(&self, levels: &mut UpgradeLevels, player: &mut Player, stats: &mut Stats) {
+        *levels.get_mut(*self) += 1;
+        if *self == Upgrade::WeaponLevel {
+            player.weapon_level += 1;
+        }
+        levels.set_modifiers(stats);
+        if *self == Upgrade::MaxHealth {
+            player.health = stats.get(Stat::MaxHealth);
+        }
+    }
+
+    /// What this many levels do to a stat, the weapon level isn't one
+    fn modifier(&self, level: u32) -> Option<(Stat, Modifier)> {
+        let level_f = level as f32;
+        let modifier = match self {
+            _ if level == 0 => return None,
+            Upgrade::MaxHealth => (Stat::MaxHealth, Modifier::Add(level_f)),
+            Upgrade::MoveSpeed => (Stat::MoveSpeed, Modifier::Multiply(1. + 0.08 * level_f)),
+            Upgrade::WeaponLevel => return None,
+            Upgrade::ExperienceGain => (
+                Stat::ExperienceGain,
+                Modifier::Multiply(1. + 0.15 * level_f),
+            ),
+            Upgrade::MagnetRange => (Stat::MagnetRange, Modifier::Add(30. * level_f)),
+        };
+        Some(modifier)
+    }
+}
+
+/// How many levels of every upgrade are bought, or on the player how many it has this run
+#[derive(Component, Serialize, Deserialize, Debug, Default, Clone, Copy)]
+#[serde(default)]
+pub struct UpgradeLevels {
+    max_health: u32,
+    move_speed: u32,
+    weapon_level: u32,
+    experience_gain: u32,
+    magnet_range: u32,
+}
+
+impl UpgradeLevels {
+    fn get_mut(&mut self, upgrade: Upgrade) -> &mut u32 {
+        match upgrade {
+            Upgrade::MaxHealth => &mut self.max_health,
+            Upgrade::MoveSpeed => &mut self.move_speed,
+            Upgrade::WeaponLevel => &mut self.weapon_level,
+            Upgrade::ExperienceGain => &mut self.experience_gain,
+            Upgrade::MagnetRange => &mut self.magnet_range,
+        }
+    }
+
+    /// Replaces the upgrade modifiers with a single one for every upgrade
+    fn set_modifiers(&self, stats: &mut Stats) {
+        let mut levels = *self;
+        stats.set(
+            ModifierSource::Upgrades,
+            Upgrade::ALL
+                .iter()
+                .filter_map(|upgrade| upgrade.modifier(*levels.get_mut(*upgrade))),
+        );
+    }
+}
+
+/// Gold and bought upgrades, kept across runs on this device
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Upgrades {
+    pub gold: u32,
+    levels: UpgradeLevels,
+}
+
+impl Upgrades {
+    fn load() -> Self {
+        let Some(contents) = storage::load(UPGRADES_FILE) else {
+            return Upgrades::default();
+        };
+        let mut upgrades: Upgrades = toml::from_str(&contents).unwrap_or_else(|error| {
+            warn!("Ignoring invalid {UPGRADES_FILE}: {error}");
+            Upgrades::default()
+        });
+        for upgrade in Upgrade::ALL {
+            let level = upgrades.levels.get_mut(upgrade);
+            *level = (*level).min(upgrade.max_level());
+        }
+        upgrades
+    }
+
+    fn save(&self) {
+        match toml::to_string(self) {
+            Ok(contents) => storage::save(UPGRADES_FILE, &contents),
+            Err(error) => warn!("Failed to save upgrades: {error}"),
+        }
+    }
+
+    /// The gold a run with this score earns
+    pub fn gold_for(score: i32) -> u32 {
+        (score.max(0) as f32 * GOLD_PER_SCORE) as u32
+    }
+
+    pub fn level(&self, upgrade: Upgrade) -> u32 {
+        let mut levels = self.levels;
+        *levels.get_mut(upgrade)
+    }
+
+    /// The price of the next level, `None` once it is maxed out
+    pub fn next_cost(&self, upgrade: Upgrade) -> Option<u32> {
+        let level = self.level(upgrade);
+        (level < upgrade.max_level()).then(|| upgrade.cost(level))
+    }
+
+    /// Returns whether there was enough gold
+    pub fn buy(&mut self, upgrade: Upgrade) -> bool {
+        let Some(cost) = self.next_cost(upgrade).filter(|cost| *cost <= self.gold) else {
+            return false;
+        };
+        self.gold -= cost;
+        *self.levels.get_mut(upgrade) += 1;
+        self.save();
+        true
+    }
+
+    /// Sets up a freshly spawned player with everything bought so far, returns the levels to
+    /// keep on the player
+    pub fn apply(&self, player: &mut Player, stats: &mut Stats) -> UpgradeLevels {
+        player.weapon_level += self.level(Upgrade::WeaponLevel) as i32;
+        self.levels.set_modifiers(stats);
+        player.health = stats.get(Stat::MaxHealth);
+        self.levels
+    }
+}
+
+impl Plugin for UpgradesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Upgrades::load())
+            .add_systems(Update, earn_gold);
+    }
+}
+
+fn earn_gold(mut upgrades: ResMut<Upgrades>, mut finished_events: EventReader<RunFinished>) {
+    for RunFinished(report) in finished_events.read() {
+        let gold = Upgrades::gold_for(report.entry.score);
+        if gold > 0 {
+            upgrades.gold += gold;
+            upgrades.save();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_gained_in_a_run_follow_the_bought_curve() {
+        let mut stats = Stats::default().with_base(Stat::MoveSpeed, 10.);
+        let mut levels = UpgradeLevels::default();
+        for _ in 0..3 {
+            *levels.get_mut(Upgrade::MoveSpeed) += 1;
+            levels.set_modifiers(&mut stats);
+        }
+        assert!((stats.get(Stat::MoveSpeed) - 10. * (1. + 0.08 * 3.)).abs() < 1e-4);
+    }
+}